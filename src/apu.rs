@@ -0,0 +1,357 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+use crate::QuirkSet;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ChannelLayout {
+    Stereo,
+    Mono,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Mixer {
+    pub nr50: u8,
+    pub nr51: u8,
+    pub layout: ChannelLayout,
+    pub high_pass_filter: Option<HighPassFilter>,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct HighPassFilter {
+    charge_factor: f32,
+    left_capacitor: f32,
+    right_capacitor: f32,
+}
+
+impl Mixer {
+    pub fn new() -> Mixer {
+        Mixer {
+            nr50: 0,
+            nr51: 0,
+            layout: ChannelLayout::Stereo,
+            high_pass_filter: None,
+        }
+    }
+
+    pub fn mix(&self, channels: [f32; 4]) -> (f32, f32) {
+        let mut left = 0.0;
+        let mut right = 0.0;
+
+        for (channel, sample) in channels.iter().enumerate() {
+            if self.nr51 & (0b00010000 << channel) != 0 {
+                left += sample;
+            }
+
+            if self.nr51 & (0b00000001 << channel) != 0 {
+                right += sample;
+            }
+        }
+
+        // Each side has a master volume of 1-8, where a setting of 0 is still audible.
+        let left_volume = ((self.nr50 >> 4) & 0b00000111) as f32 + 1.0;
+        let right_volume = (self.nr50 & 0b00000111) as f32 + 1.0;
+
+        (
+            left / 4.0 * left_volume / 8.0,
+            right / 4.0 * right_volume / 8.0,
+        )
+    }
+
+    pub fn output(&mut self, channels: &[[f32; 4]], buffer: &mut Vec<f32>) {
+        for frame in channels {
+            let mut sample = self.mix(*frame);
+
+            if let Some(filter) = &mut self.high_pass_filter {
+                sample = filter.filter(sample);
+            }
+
+            let (left, right) = sample;
+
+            match self.layout {
+                ChannelLayout::Stereo => {
+                    buffer.push(left);
+                    buffer.push(right);
+                }
+                ChannelLayout::Mono => buffer.push((left + right) / 2.0),
+            }
+        }
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HighPassFilter {
+    const CLOCK_RATE: f32 = 4194304.0;
+
+    // The charge factors are given per T-cycle, so they need to be scaled to the number of
+    // T-cycles that elapse between two output samples.
+    pub fn new(quirks: &QuirkSet, sample_rate: u32) -> HighPassFilter {
+        let charge_factor = quirks.high_pass_charge_factor;
+
+        HighPassFilter {
+            charge_factor: charge_factor.powf(Self::CLOCK_RATE / sample_rate as f32),
+            left_capacitor: 0.0,
+            right_capacitor: 0.0,
+        }
+    }
+
+    pub fn filter(&mut self, sample: (f32, f32)) -> (f32, f32) {
+        let (left, right) = sample;
+
+        let left_out = left - self.left_capacitor;
+        self.left_capacitor = left - left_out * self.charge_factor;
+
+        let right_out = right - self.right_capacitor;
+        self.right_capacitor = right - right_out * self.charge_factor;
+
+        (left_out, right_out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    use crate::HardwareModel;
+
+    #[test]
+    fn should_initialize_registers_to_0() {
+        let mixer = Mixer::new();
+        assert_eq!(mixer.nr50, 0);
+        assert_eq!(mixer.nr51, 0);
+    }
+
+    #[test]
+    fn should_initialize_layout_to_stereo() {
+        let mixer = Mixer::new();
+        assert_eq!(mixer.layout, ChannelLayout::Stereo);
+    }
+
+    #[test]
+    fn should_initialize_layout_to_stereo_by_default() {
+        let mixer = Mixer::default();
+        assert_eq!(mixer.layout, ChannelLayout::Stereo);
+    }
+
+    #[test]
+    fn should_initialize_high_pass_filter_to_none() {
+        let mixer = Mixer::new();
+        assert_eq!(mixer.high_pass_filter, None);
+    }
+
+    #[test]
+    fn should_clone_mixer_with_filter_state() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b11111111;
+        mixer.high_pass_filter = Some(HighPassFilter::new(&HardwareModel::Dmg.quirks(), 44100));
+        mixer.output(&[[1.0, 1.0, 1.0, 1.0]], &mut Vec::new());
+
+        let mut clone = mixer.clone();
+        assert_eq!(clone, mixer);
+
+        let mut expected = Vec::new();
+        let mut actual = Vec::new();
+        mixer.output(&[[1.0, 1.0, 1.0, 1.0]], &mut expected);
+        clone.output(&[[1.0, 1.0, 1.0, 1.0]], &mut actual);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn should_output_silence_when_no_channels_are_routed() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b00000000;
+
+        assert_eq!(mixer.mix([1.0, 1.0, 1.0, 1.0]), (0.0, 0.0));
+    }
+
+    #[rstest]
+    #[case(0, 0b00010000)]
+    #[case(1, 0b00100000)]
+    #[case(2, 0b01000000)]
+    #[case(3, 0b10000000)]
+    fn should_only_route_channel_to_left_when_left_bit_is_set(
+        #[case] channel: usize,
+        #[case] nr51: u8,
+    ) {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = nr51;
+
+        let mut channels = [0.0; 4];
+        channels[channel] = 1.0;
+
+        assert_eq!(mixer.mix(channels), (0.25, 0.0));
+    }
+
+    #[rstest]
+    #[case(0, 0b00000001)]
+    #[case(1, 0b00000010)]
+    #[case(2, 0b00000100)]
+    #[case(3, 0b00001000)]
+    fn should_only_route_channel_to_right_when_right_bit_is_set(
+        #[case] channel: usize,
+        #[case] nr51: u8,
+    ) {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = nr51;
+
+        let mut channels = [0.0; 4];
+        channels[channel] = 1.0;
+
+        assert_eq!(mixer.mix(channels), (0.0, 0.25));
+    }
+
+    #[test]
+    fn should_output_full_scale_when_all_channels_are_routed_at_max_volume() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b11111111;
+
+        assert_eq!(mixer.mix([1.0, 1.0, 1.0, 1.0]), (1.0, 1.0));
+        assert_eq!(mixer.mix([-1.0, -1.0, -1.0, -1.0]), (-1.0, -1.0));
+    }
+
+    #[rstest]
+    #[case(0b00000000, 0.125, 0.125)]
+    #[case(0b00110000, 0.5, 0.125)]
+    #[case(0b00000011, 0.125, 0.5)]
+    #[case(0b01110111, 1.0, 1.0)]
+    #[case(0b11111111, 1.0, 1.0)]
+    fn should_scale_each_side_by_master_volume(
+        #[case] nr50: u8,
+        #[case] left: f32,
+        #[case] right: f32,
+    ) {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = nr50;
+        mixer.nr51 = 0b11111111;
+
+        assert_eq!(mixer.mix([1.0, 1.0, 1.0, 1.0]), (left, right));
+    }
+
+    #[test]
+    fn should_output_interleaved_stereo_frames() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b00010010;
+
+        let mut buffer = Vec::new();
+        mixer.output(&[[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0]], &mut buffer);
+
+        assert_eq!(buffer, vec![0.25, 0.0, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn should_output_one_sample_per_frame_when_mono() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b00010011;
+        mixer.layout = ChannelLayout::Mono;
+
+        let mut buffer = Vec::new();
+        mixer.output(&[[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0]], &mut buffer);
+
+        assert_eq!(buffer, vec![0.25, 0.125]);
+    }
+
+    #[test]
+    fn should_append_to_existing_output() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b00010001;
+
+        let mut buffer = vec![0.5];
+        mixer.output(&[[1.0, 0.0, 0.0, 0.0]], &mut buffer);
+
+        assert_eq!(buffer, vec![0.5, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn should_pass_first_sample_through_high_pass_filter_unchanged() {
+        let mut filter = HighPassFilter::new(&HardwareModel::Dmg.quirks(), 44100);
+        assert_eq!(filter.filter((0.5, -0.5)), (0.5, -0.5));
+    }
+
+    #[rstest]
+    #[case(HighPassFilter::new(&HardwareModel::Dmg.quirks(), 44100))]
+    #[case(HighPassFilter::new(&HardwareModel::Cgb.quirks(), 44100))]
+    fn should_remove_dc_offset_over_time(#[case] mut filter: HighPassFilter) {
+        let mut sample = (0.0, 0.0);
+        for _ in 0..44100 {
+            sample = filter.filter((0.5, -0.5));
+        }
+
+        assert!(sample.0.abs() < 0.001);
+        assert!(sample.1.abs() < 0.001);
+    }
+
+    #[test]
+    fn should_decay_faster_on_cgb_than_dmg() {
+        let mut dmg = HighPassFilter::new(&HardwareModel::Dmg.quirks(), 44100);
+        let mut cgb = HighPassFilter::new(&HardwareModel::Cgb.quirks(), 44100);
+
+        let mut dmg_sample = (0.0, 0.0);
+        let mut cgb_sample = (0.0, 0.0);
+        for _ in 0..10 {
+            dmg_sample = dmg.filter((1.0, 1.0));
+            cgb_sample = cgb.filter((1.0, 1.0));
+        }
+
+        assert!(cgb_sample.0 < dmg_sample.0);
+        assert!(cgb_sample.1 < dmg_sample.1);
+    }
+
+    #[test]
+    fn should_filter_each_side_independently() {
+        let mut filter = HighPassFilter::new(&HardwareModel::Dmg.quirks(), 44100);
+
+        filter.filter((1.0, 0.0));
+        let (left, right) = filter.filter((1.0, 0.0));
+
+        assert!(left < 1.0);
+        assert_eq!(right, 0.0);
+    }
+
+    #[test]
+    fn should_apply_high_pass_filter_to_output() {
+        let mut mixer = Mixer::new();
+        mixer.nr50 = 0b01110111;
+        mixer.nr51 = 0b11111111;
+        mixer.high_pass_filter = Some(HighPassFilter::new(&HardwareModel::Dmg.quirks(), 44100));
+
+        let mut buffer = Vec::new();
+        mixer.output(&[[1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 1.0]], &mut buffer);
+
+        assert_eq!(buffer[0], 1.0);
+        assert_eq!(buffer[1], 1.0);
+        assert!(buffer[2] < 1.0);
+        assert!(buffer[3] < 1.0);
+    }
+}
@@ -0,0 +1,80 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+use crate::{HardwareModel, QuirkSet};
+
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+pub fn features() -> &'static [&'static str] {
+    if cfg!(feature = "log") {
+        &["log"]
+    } else {
+        &[]
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub features: &'static [&'static str],
+    pub model: HardwareModel,
+    pub quirks: QuirkSet,
+}
+
+impl BuildInfo {
+    pub fn new(model: HardwareModel) -> BuildInfo {
+        BuildInfo {
+            version: version(),
+            features: features(),
+            model,
+            quirks: model.quirks(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    #[test]
+    fn should_return_crate_version() {
+        assert_eq!(version(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn should_report_log_feature_only_when_enabled() {
+        assert_eq!(features().contains(&"log"), cfg!(feature = "log"));
+    }
+
+    #[rstest]
+    #[case(HardwareModel::Dmg)]
+    #[case(HardwareModel::Mgb)]
+    #[case(HardwareModel::Cgb)]
+    fn should_report_model_and_its_quirks(#[case] model: HardwareModel) {
+        let info = BuildInfo::new(model);
+        assert_eq!(info.version, version());
+        assert_eq!(info.features, features());
+        assert_eq!(info.model, model);
+        assert_eq!(info.quirks, model.quirks());
+    }
+}
@@ -23,6 +23,7 @@ pub enum ReadWriteMode {
     Write,
 }
 
+#[derive(PartialEq, Debug, Clone)]
 pub struct Bus {
     pub data: u8,
     pub address: u16,
@@ -85,6 +86,16 @@ mod tests {
         assert_eq!(bus.mode, ReadWriteMode::Read);
     }
 
+    #[test]
+    fn should_clone_bus() {
+        let mut bus = Bus::new();
+        bus.data = 0x42;
+        bus.address = 0x1234;
+        bus.mode = ReadWriteMode::Write;
+
+        assert_eq!(bus.clone(), bus);
+    }
+
     #[test]
     fn should_be_5_when_set_to_5() {
         let mut bus = Bus::new();
@@ -17,16 +17,17 @@
     with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
 */
 
-use crate::opcode::{Opcode, Register8Bit};
+use crate::opcode::{MCycle, Opcode, Register16Bit, Register8Bit};
 use crate::{Bus, ReadWriteMode};
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct SharpSM83 {
     pub registers: Registers,
     current_tick: u8,
     opcode: Opcode,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Registers {
     pub a: u8,
     pub b: u8,
@@ -40,6 +41,14 @@ pub struct Registers {
     pub program_counter: u16,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Flags {
+    pub z: bool,
+    pub n: bool,
+    pub h: bool,
+    pub c: bool,
+}
+
 impl SharpSM83 {
     pub fn new() -> SharpSM83 {
         SharpSM83 {
@@ -60,6 +69,10 @@ impl SharpSM83 {
         }
     }
 
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
     pub fn tick(&mut self, bus: &mut Bus) {
         match self.current_tick {
             1 => self.write_program_counter(bus),
@@ -77,51 +90,155 @@ impl SharpSM83 {
 
     fn read_opcode(&mut self, bus: &mut Bus) {
         self.opcode = Opcode::decode(bus.data);
+
+        #[cfg(feature = "log")]
+        if self.opcode.m_cycle(0).is_none() {
+            log::warn!(
+                "Unimplemented opcode {:?} ({:#04x}) at {:#06x}",
+                self.opcode,
+                bus.data,
+                self.registers.program_counter
+            );
+        }
     }
 
     fn increment_program_counter(&mut self) {
         self.registers.program_counter += 1;
     }
 
+    // Each M-cycle is 4 ticks long. Bus requests go out on its first tick and data is latched on
+    // its last.
     fn execute_opcode(&mut self, bus: &mut Bus) {
-        match self.opcode {
-            Opcode::Nop => self.no_op(),
-            Opcode::LdReg8Imm8(dest) => self.ld_r_n8(dest, bus),
-            Opcode::Unimplemented(_) => {}
-            _ => {}
+        let index = (self.current_tick - 1) / 4;
+        let phase = (self.current_tick - 1) % 4;
+
+        let Some(m_cycle) = self.opcode.m_cycle(index) else {
+            return;
+        };
+
+        match (m_cycle, phase) {
+            (MCycle::ReadImm8(_), 0) => self.request_immediate(bus),
+            (MCycle::ReadImm8(destination), 3) => self.load_immediate(destination, bus),
+            _ => (),
+        }
+
+        if phase == 3 && self.opcode.m_cycle(index + 1).is_none() {
+            self.current_tick = 0;
         }
     }
 
-    fn no_op(&mut self) {
-        self.current_tick = 0;
+    fn request_immediate(&mut self, bus: &mut Bus) {
+        bus.mode = ReadWriteMode::Read;
+        bus.address = self.registers.program_counter;
     }
 
-    fn ld_r_n8(&mut self, destination: Register8Bit, bus: &mut Bus) {
-        match self.current_tick {
-            5 => {
-                bus.mode = ReadWriteMode::Read;
-                bus.address = self.registers.program_counter;
-            }
-            8 => {
-                self.write_to_register(destination, bus.data);
-                self.increment_program_counter();
-                self.current_tick = 0;
-            }
-            _ => (),
+    fn load_immediate(&mut self, destination: Register8Bit, bus: &mut Bus) {
+        let written = self.registers.set8(destination, bus.data);
+        debug_assert!(written, "LD r, n8 decoded with [HL] as its destination");
+        self.increment_program_counter();
+    }
+}
+
+impl Registers {
+    // [HL] is a memory operand rather than a register, so it can't be read or written here.
+    pub fn get8(&self, register: Register8Bit) -> Option<u8> {
+        match register {
+            Register8Bit::A => Some(self.a),
+            Register8Bit::B => Some(self.b),
+            Register8Bit::C => Some(self.c),
+            Register8Bit::D => Some(self.d),
+            Register8Bit::E => Some(self.e),
+            Register8Bit::H => Some(self.h),
+            Register8Bit::L => Some(self.l),
+            Register8Bit::HLAddr => None,
         }
     }
 
-    fn write_to_register(&mut self, dest: Register8Bit, data: u8) {
-        match dest {
-            Register8Bit::A => self.registers.a = data,
-            Register8Bit::B => self.registers.b = data,
-            Register8Bit::C => self.registers.c = data,
-            Register8Bit::D => self.registers.d = data,
-            Register8Bit::E => self.registers.e = data,
-            Register8Bit::H => self.registers.h = data,
-            Register8Bit::L => self.registers.l = data,
-            _ => panic!("Tried to write to invalid destination"),
+    #[must_use]
+    pub fn set8(&mut self, register: Register8Bit, data: u8) -> bool {
+        match register {
+            Register8Bit::A => self.a = data,
+            Register8Bit::B => self.b = data,
+            Register8Bit::C => self.c = data,
+            Register8Bit::D => self.d = data,
+            Register8Bit::E => self.e = data,
+            Register8Bit::H => self.h = data,
+            Register8Bit::L => self.l = data,
+            Register8Bit::HLAddr => return false,
         };
+
+        true
+    }
+
+    pub fn get16(&self, register: Register16Bit) -> u16 {
+        match register {
+            Register16Bit::BC => u16::from_be_bytes([self.b, self.c]),
+            Register16Bit::DE => u16::from_be_bytes([self.d, self.e]),
+            Register16Bit::HL => u16::from_be_bytes([self.h, self.l]),
+            Register16Bit::SP => self.stack_pointer,
+        }
+    }
+
+    pub fn set16(&mut self, register: Register16Bit, data: u16) {
+        let [high, low] = data.to_be_bytes();
+
+        match register {
+            Register16Bit::BC => (self.b, self.c) = (high, low),
+            Register16Bit::DE => (self.d, self.e) = (high, low),
+            Register16Bit::HL => (self.h, self.l) = (high, low),
+            Register16Bit::SP => self.stack_pointer = data,
+        };
+    }
+
+    pub fn af(&self) -> u16 {
+        u16::from_be_bytes([self.a, self.f])
+    }
+
+    pub fn set_af(&mut self, data: u16) {
+        let [a, f] = data.to_be_bytes();
+        self.a = a;
+        // The lower 4 bits of F are always 0 on hardware.
+        self.f = f & 0b11110000;
+    }
+
+    pub fn bc(&self) -> u16 {
+        self.get16(Register16Bit::BC)
+    }
+
+    pub fn set_bc(&mut self, data: u16) {
+        self.set16(Register16Bit::BC, data);
+    }
+
+    pub fn de(&self) -> u16 {
+        self.get16(Register16Bit::DE)
+    }
+
+    pub fn set_de(&mut self, data: u16) {
+        self.set16(Register16Bit::DE, data);
+    }
+
+    pub fn hl(&self) -> u16 {
+        self.get16(Register16Bit::HL)
+    }
+
+    pub fn set_hl(&mut self, data: u16) {
+        self.set16(Register16Bit::HL, data);
+    }
+
+    pub fn flags(&self) -> Flags {
+        Flags {
+            z: self.f & 0b10000000 != 0,
+            n: self.f & 0b01000000 != 0,
+            h: self.f & 0b00100000 != 0,
+            c: self.f & 0b00010000 != 0,
+        }
+    }
+
+    pub fn set_flags(&mut self, flags: Flags) {
+        self.f = (flags.z as u8) << 7
+            | (flags.n as u8) << 6
+            | (flags.h as u8) << 5
+            | (flags.c as u8) << 4;
     }
 }
 
@@ -137,7 +254,10 @@ mod tests {
 
     use super::*;
 
-    use crate::{opcode::Register8Bit, ReadWriteMode};
+    use crate::{
+        opcode::{Register16Bit, Register8Bit},
+        ReadWriteMode,
+    };
 
     #[test]
     #[allow(clippy::bool_assert_comparison)]
@@ -222,6 +342,265 @@ mod tests {
         assert_eq!(clone, registers);
     }
 
+    #[test]
+    fn should_initialize_registers_to_0_when_defaulted() {
+        let registers = Registers::default();
+        assert_eq!(registers, SharpSM83::new().registers);
+    }
+
+    #[test]
+    fn should_initialize_flags_to_false_by_default() {
+        let flags = Flags::default();
+        assert_eq!(
+            flags,
+            Flags {
+                z: false,
+                n: false,
+                h: false,
+                c: false
+            }
+        );
+    }
+
+    #[test]
+    fn should_clone_cpu_mid_instruction() {
+        let mut cpu = SharpSM83::new();
+        let mut bus = Bus::new();
+
+        bus.data = 0b00111110;
+        cpu.tick(&mut bus);
+        cpu.tick(&mut bus);
+        cpu.tick(&mut bus);
+
+        let mut clone = cpu.clone();
+        assert_eq!(clone, cpu);
+
+        bus.data = 0x42;
+        for _ in 0..5 {
+            cpu.tick(&mut bus);
+            clone.tick(&mut bus);
+        }
+
+        assert_eq!(clone, cpu);
+        assert_eq!(clone.registers.a, 0x42);
+    }
+
+    #[test]
+    fn should_not_equal_cpu_at_different_tick() {
+        let mut cpu = SharpSM83::new();
+        let mut bus = Bus::new();
+
+        let clone = cpu.clone();
+        cpu.tick(&mut bus);
+
+        assert_ne!(clone, cpu);
+    }
+
+    #[rstest]
+    #[case(Register8Bit::A)]
+    #[case(Register8Bit::B)]
+    #[case(Register8Bit::C)]
+    #[case(Register8Bit::D)]
+    #[case(Register8Bit::E)]
+    #[case(Register8Bit::H)]
+    #[case(Register8Bit::L)]
+    fn should_get_the_8_bit_register_that_was_set(#[case] register: Register8Bit) {
+        let mut cpu = SharpSM83::new();
+        assert!(cpu.registers.set8(register, 0x42));
+        assert_eq!(cpu.registers.get8(register), Some(0x42));
+    }
+
+    #[rstest]
+    #[case(Register8Bit::A, 0x01)]
+    #[case(Register8Bit::B, 0x02)]
+    #[case(Register8Bit::C, 0x03)]
+    #[case(Register8Bit::D, 0x04)]
+    #[case(Register8Bit::E, 0x05)]
+    #[case(Register8Bit::H, 0x06)]
+    #[case(Register8Bit::L, 0x07)]
+    fn should_get_8_bit_register_by_enum(#[case] register: Register8Bit, #[case] expected: u8) {
+        let registers = Registers {
+            a: 0x01,
+            b: 0x02,
+            c: 0x03,
+            d: 0x04,
+            e: 0x05,
+            f: 0x00,
+            h: 0x06,
+            l: 0x07,
+            stack_pointer: 0,
+            program_counter: 0,
+        };
+
+        assert_eq!(registers.get8(register), Some(expected));
+    }
+
+    #[test]
+    fn should_not_get_hl_addr_as_8_bit_register() {
+        let cpu = SharpSM83::new();
+        assert_eq!(cpu.registers.get8(Register8Bit::HLAddr), None);
+    }
+
+    #[test]
+    fn should_not_set_hl_addr_as_8_bit_register() {
+        let mut cpu = SharpSM83::new();
+        let registers = cpu.registers.clone();
+
+        assert!(!cpu.registers.set8(Register8Bit::HLAddr, 0x42));
+        assert_eq!(cpu.registers, registers);
+    }
+
+    #[rstest]
+    #[case(Register16Bit::BC, 0x0203)]
+    #[case(Register16Bit::DE, 0x0405)]
+    #[case(Register16Bit::HL, 0x0607)]
+    #[case(Register16Bit::SP, 0x1234)]
+    fn should_get_16_bit_register_by_enum(#[case] register: Register16Bit, #[case] expected: u16) {
+        let registers = Registers {
+            a: 0x01,
+            b: 0x02,
+            c: 0x03,
+            d: 0x04,
+            e: 0x05,
+            f: 0x00,
+            h: 0x06,
+            l: 0x07,
+            stack_pointer: 0x1234,
+            program_counter: 0,
+        };
+
+        assert_eq!(registers.get16(register), expected);
+    }
+
+    #[rstest]
+    #[case(Register16Bit::BC, Register8Bit::B, Register8Bit::C)]
+    #[case(Register16Bit::DE, Register8Bit::D, Register8Bit::E)]
+    #[case(Register16Bit::HL, Register8Bit::H, Register8Bit::L)]
+    fn should_set_high_and_low_bytes_of_16_bit_register(
+        #[case] register: Register16Bit,
+        #[case] high: Register8Bit,
+        #[case] low: Register8Bit,
+    ) {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set16(register, 0xABCD);
+
+        assert_eq!(cpu.registers.get8(high), Some(0xAB));
+        assert_eq!(cpu.registers.get8(low), Some(0xCD));
+        assert_eq!(cpu.registers.get16(register), 0xABCD);
+    }
+
+    #[test]
+    fn should_set_stack_pointer_as_16_bit_register() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set16(Register16Bit::SP, 0xFFFE);
+        assert_eq!(cpu.registers.stack_pointer, 0xFFFE);
+    }
+
+    #[test]
+    fn should_pack_a_and_f_into_af() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.a = 0x12;
+        cpu.registers.f = 0xB0;
+        assert_eq!(cpu.registers.af(), 0x12B0);
+    }
+
+    #[test]
+    fn should_unpack_af_into_a_and_f() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set_af(0x12B0);
+        assert_eq!(cpu.registers.a, 0x12);
+        assert_eq!(cpu.registers.f, 0xB0);
+    }
+
+    #[test]
+    fn should_mask_lower_bits_of_f_when_setting_af() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set_af(0x12FF);
+        assert_eq!(cpu.registers.f, 0xF0);
+        assert_eq!(cpu.registers.af(), 0x12F0);
+    }
+
+    #[test]
+    fn should_pack_and_unpack_bc() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set_bc(0x1234);
+        assert_eq!(cpu.registers.b, 0x12);
+        assert_eq!(cpu.registers.c, 0x34);
+        assert_eq!(cpu.registers.bc(), 0x1234);
+    }
+
+    #[test]
+    fn should_pack_and_unpack_de() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set_de(0x1234);
+        assert_eq!(cpu.registers.d, 0x12);
+        assert_eq!(cpu.registers.e, 0x34);
+        assert_eq!(cpu.registers.de(), 0x1234);
+    }
+
+    #[test]
+    fn should_pack_and_unpack_hl() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set_hl(0x1234);
+        assert_eq!(cpu.registers.h, 0x12);
+        assert_eq!(cpu.registers.l, 0x34);
+        assert_eq!(cpu.registers.hl(), 0x1234);
+    }
+
+    #[rstest]
+    #[case(0b00000000, false, false, false, false)]
+    #[case(0b10000000, true, false, false, false)]
+    #[case(0b01000000, false, true, false, false)]
+    #[case(0b00100000, false, false, true, false)]
+    #[case(0b00010000, false, false, false, true)]
+    #[case(0b11110000, true, true, true, true)]
+    #[case(0b10101111, true, false, true, false)]
+    fn should_decode_flags_from_f(
+        #[case] f: u8,
+        #[case] z: bool,
+        #[case] n: bool,
+        #[case] h: bool,
+        #[case] c: bool,
+    ) {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.f = f;
+        assert_eq!(cpu.registers.flags(), Flags { z, n, h, c });
+    }
+
+    #[rstest]
+    #[case(false, false, false, false, 0b00000000)]
+    #[case(true, false, false, false, 0b10000000)]
+    #[case(false, true, false, false, 0b01000000)]
+    #[case(false, false, true, false, 0b00100000)]
+    #[case(false, false, false, true, 0b00010000)]
+    #[case(true, true, true, true, 0b11110000)]
+    fn should_encode_flags_into_f(
+        #[case] z: bool,
+        #[case] n: bool,
+        #[case] h: bool,
+        #[case] c: bool,
+        #[case] expected: u8,
+    ) {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.set_flags(Flags { z, n, h, c });
+        assert_eq!(cpu.registers.f, expected);
+    }
+
+    #[test]
+    fn should_clear_lower_bits_of_f_when_setting_flags() {
+        let mut cpu = SharpSM83::new();
+        cpu.registers.f = 0b00001111;
+
+        cpu.registers.set_flags(Flags {
+            z: true,
+            n: false,
+            h: false,
+            c: false,
+        });
+
+        assert_eq!(cpu.registers.f, 0b10000000);
+    }
+
     #[test]
     fn should_initialize_registers_to_0() {
         let cpu = SharpSM83::new();
@@ -328,6 +707,67 @@ mod tests {
         assert_eq!(expected_registers, cpu.registers);
     }
 
+    #[test]
+    fn should_advance_program_counter_by_opcode_length_for_every_implemented_opcode() {
+        for data in 0..=u8::MAX {
+            let opcode = Opcode::decode(data);
+            if opcode.m_cycle(0).is_none() {
+                continue;
+            }
+
+            let mut cpu = SharpSM83::new();
+            let mut bus = Bus::new();
+
+            cpu.registers.program_counter = 0x1000;
+            bus.data = data;
+
+            cpu.tick(&mut bus);
+            while cpu.current_tick != 1 {
+                cpu.tick(&mut bus);
+            }
+
+            assert_eq!(
+                cpu.registers.program_counter,
+                0x1000 + opcode.length().unwrap() as u16,
+                "opcode {data:#04x}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_take_4_cycles_per_explained_m_cycle() {
+        for data in 0..=u8::MAX {
+            let Some(explanation) = Opcode::decode(data).explain() else {
+                continue;
+            };
+
+            let mut cpu = SharpSM83::new();
+            let mut bus = Bus::new();
+            bus.data = data;
+
+            cpu.tick(&mut bus);
+            let mut cycles = 1;
+            while cpu.current_tick != 1 {
+                cpu.tick(&mut bus);
+                cycles += 1;
+            }
+
+            assert_eq!(cycles, explanation.len() * 4, "opcode {data:#04x}");
+        }
+    }
+
+    #[test]
+    fn should_expose_decoded_opcode() {
+        let mut cpu = SharpSM83::new();
+        let mut bus = Bus::new();
+        bus.data = 0b00111110;
+
+        cpu.tick(&mut bus);
+        cpu.tick(&mut bus);
+
+        assert_eq!(cpu.opcode(), Opcode::LdReg8Imm8(Register8Bit::A));
+    }
+
     #[test]
     fn should_write_program_counter_after_no_op() {
         let mut cpu = SharpSM83::new();
@@ -0,0 +1,288 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+use crate::{unused_bits, Joypad, Serial, Timer, IF};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Interrupt {
+    VBlank,
+    Stat,
+    Timer,
+    Serial,
+    Joypad,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Interrupts {
+    pub enable: u8,
+    flag: u8,
+}
+
+impl Interrupt {
+    const PRIORITY: [Interrupt; 5] = [
+        Interrupt::VBlank,
+        Interrupt::Stat,
+        Interrupt::Timer,
+        Interrupt::Serial,
+        Interrupt::Joypad,
+    ];
+
+    pub fn mask(&self) -> u8 {
+        match self {
+            Interrupt::VBlank => 0b00000001,
+            Interrupt::Stat => 0b00000010,
+            Interrupt::Timer => 0b00000100,
+            Interrupt::Serial => 0b00001000,
+            Interrupt::Joypad => 0b00010000,
+        }
+    }
+}
+
+impl Interrupts {
+    pub fn new() -> Interrupts {
+        Interrupts { enable: 0, flag: 0 }
+    }
+
+    pub fn flag(&self) -> u8 {
+        unused_bits(IF) | self.flag
+    }
+
+    pub fn write_flag(&mut self, data: u8) {
+        self.flag = data & 0b00011111;
+    }
+
+    pub fn request(&mut self, interrupt: Interrupt) {
+        self.flag |= interrupt.mask();
+    }
+
+    pub fn acknowledge(&mut self, interrupt: Interrupt) {
+        self.flag &= !interrupt.mask();
+    }
+
+    // Devices raise their own request line. Collecting clears the line and latches it into IF,
+    // so pending checks only need to look at the controller.
+    pub fn collect_requests(
+        &mut self,
+        timer: &mut Timer,
+        serial: &mut Serial,
+        joypad: &mut Joypad,
+    ) {
+        self.collect(Interrupt::Timer, &mut timer.interrupt_requested);
+        self.collect(Interrupt::Serial, &mut serial.interrupt_requested);
+        self.collect(Interrupt::Joypad, &mut joypad.interrupt_requested);
+    }
+
+    fn collect(&mut self, interrupt: Interrupt, requested: &mut bool) {
+        if *requested {
+            self.request(interrupt);
+            *requested = false;
+        }
+    }
+
+    pub fn pending(&self) -> Option<Interrupt> {
+        let pending = self.flag & self.enable;
+
+        Interrupt::PRIORITY
+            .into_iter()
+            .find(|interrupt| pending & interrupt.mask() != 0)
+    }
+}
+
+impl Default for Interrupts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    use crate::{Button, HardwareModel};
+
+    #[test]
+    fn should_initialize_to_nothing_enabled_or_requested() {
+        let interrupts = Interrupts::new();
+        assert_eq!(interrupts.enable, 0);
+        assert_eq!(interrupts.flag(), 0b11100000);
+    }
+
+    #[test]
+    fn should_initialize_to_nothing_enabled_or_requested_by_default() {
+        let interrupts = Interrupts::default();
+        assert_eq!(interrupts.enable, 0);
+        assert_eq!(interrupts.flag(), 0b11100000);
+    }
+
+    #[test]
+    fn should_clone_interrupts() {
+        let mut interrupts = Interrupts::new();
+        interrupts.enable = 0b00000101;
+        interrupts.request(Interrupt::Timer);
+
+        assert_eq!(interrupts.clone(), interrupts);
+    }
+
+    #[rstest]
+    #[case(0b00000000, 0b11100000)]
+    #[case(0b00010101, 0b11110101)]
+    #[case(0b11111111, 0b11111111)]
+    #[case(0b11100000, 0b11100000)]
+    fn should_read_upper_bits_of_if_as_1(#[case] data: u8, #[case] expected: u8) {
+        let mut interrupts = Interrupts::new();
+        interrupts.write_flag(data);
+        assert_eq!(interrupts.flag(), expected);
+    }
+
+    #[test]
+    fn should_clear_requested_interrupts_when_if_is_written() {
+        let mut interrupts = Interrupts::new();
+        interrupts.request(Interrupt::Timer);
+        interrupts.request(Interrupt::Joypad);
+
+        interrupts.write_flag(0b00010000);
+
+        assert_eq!(interrupts.flag(), 0b11110000);
+    }
+
+    #[rstest]
+    #[case(Interrupt::VBlank, 0b11100001)]
+    #[case(Interrupt::Stat, 0b11100010)]
+    #[case(Interrupt::Timer, 0b11100100)]
+    #[case(Interrupt::Serial, 0b11101000)]
+    #[case(Interrupt::Joypad, 0b11110000)]
+    fn should_set_if_bit_when_interrupt_is_requested(
+        #[case] interrupt: Interrupt,
+        #[case] expected: u8,
+    ) {
+        let mut interrupts = Interrupts::new();
+        interrupts.request(interrupt);
+        assert_eq!(interrupts.flag(), expected);
+    }
+
+    #[test]
+    fn should_clear_if_bit_when_interrupt_is_acknowledged() {
+        let mut interrupts = Interrupts::new();
+        interrupts.write_flag(0b00011111);
+
+        interrupts.acknowledge(Interrupt::Timer);
+
+        assert_eq!(interrupts.flag(), 0b11111011);
+    }
+
+    // Based on mooneye's if_ie_registers test: a request is only pending once it is also enabled.
+    #[test]
+    fn should_not_be_pending_when_requested_but_not_enabled() {
+        let mut interrupts = Interrupts::new();
+        interrupts.write_flag(0b00011111);
+        assert_eq!(interrupts.pending(), None);
+    }
+
+    #[test]
+    fn should_not_be_pending_when_enabled_but_not_requested() {
+        let mut interrupts = Interrupts::new();
+        interrupts.enable = 0b00011111;
+        assert_eq!(interrupts.pending(), None);
+    }
+
+    #[test]
+    fn should_be_pending_when_if_is_written_with_enabled_bit() {
+        let mut interrupts = Interrupts::new();
+        interrupts.enable = 0b00000100;
+
+        interrupts.write_flag(0b00000100);
+
+        assert_eq!(interrupts.pending(), Some(Interrupt::Timer));
+    }
+
+    #[rstest]
+    #[case(0b00011111, Interrupt::VBlank)]
+    #[case(0b00011110, Interrupt::Stat)]
+    #[case(0b00011100, Interrupt::Timer)]
+    #[case(0b00011000, Interrupt::Serial)]
+    #[case(0b00010000, Interrupt::Joypad)]
+    fn should_return_highest_priority_pending_interrupt(
+        #[case] flag: u8,
+        #[case] expected: Interrupt,
+    ) {
+        let mut interrupts = Interrupts::new();
+        interrupts.enable = 0b00011111;
+        interrupts.write_flag(flag);
+        assert_eq!(interrupts.pending(), Some(expected));
+    }
+
+    #[test]
+    fn should_ignore_upper_ie_bits_when_checking_pending() {
+        let mut interrupts = Interrupts::new();
+        interrupts.enable = 0b11100000;
+        interrupts.write_flag(0b11111111);
+        assert_eq!(interrupts.pending(), None);
+    }
+
+    #[test]
+    fn should_collect_device_requests_into_if() {
+        let mut interrupts = Interrupts::new();
+        let mut timer = Timer::new();
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        let mut joypad = Joypad::new();
+
+        timer.interrupt_requested = true;
+        joypad.write(0b00010000);
+        joypad.press(Button::Start);
+
+        interrupts.collect_requests(&mut timer, &mut serial, &mut joypad);
+
+        assert_eq!(interrupts.flag(), 0b11110100);
+        assert!(!timer.interrupt_requested);
+        assert!(!serial.interrupt_requested);
+        assert!(!joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_keep_existing_requests_when_collecting() {
+        let mut interrupts = Interrupts::new();
+        let mut timer = Timer::new();
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        let mut joypad = Joypad::new();
+
+        interrupts.request(Interrupt::VBlank);
+        serial.interrupt_requested = true;
+
+        interrupts.collect_requests(&mut timer, &mut serial, &mut joypad);
+
+        assert_eq!(interrupts.flag(), 0b11101001);
+    }
+
+    #[test]
+    fn should_make_collected_request_pending_when_enabled() {
+        let mut interrupts = Interrupts::new();
+        let mut timer = Timer::new();
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        let mut joypad = Joypad::new();
+
+        interrupts.enable = 0b00000100;
+        timer.interrupt_requested = true;
+
+        interrupts.collect_requests(&mut timer, &mut serial, &mut joypad);
+
+        assert_eq!(interrupts.pending(), Some(Interrupt::Timer));
+    }
+}
@@ -0,0 +1,117 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+pub const P1: u16 = 0xFF00;
+pub const SC: u16 = 0xFF02;
+pub const TAC: u16 = 0xFF07;
+pub const IF: u16 = 0xFF0F;
+
+// Bits that don't exist in an IO register always read back as 1. These are the DMG masks, models
+// with extra bits clear them in the device itself.
+pub const UNUSED_IO_BITS: [(u16, u8); 4] = [
+    (P1, 0b11000000),
+    (SC, 0b01111110),
+    (TAC, 0b11111000),
+    (IF, 0b11100000),
+];
+
+pub fn unused_bits(address: u16) -> u8 {
+    UNUSED_IO_BITS
+        .iter()
+        .find(|(register, _)| *register == address)
+        .map_or(0, |(_, bits)| *bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    use crate::{HardwareModel, Interrupts, Joypad, Serial, Timer};
+
+    fn read_after_write(address: u16, data: u8) -> u8 {
+        match address {
+            P1 => {
+                let mut joypad = Joypad::new();
+                joypad.write(data);
+                joypad.read()
+            }
+            SC => {
+                let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+                serial.write_sc(data);
+                serial.sc()
+            }
+            TAC => {
+                let mut timer = Timer::new();
+                timer.write_tac(data);
+                timer.tac()
+            }
+            IF => {
+                let mut interrupts = Interrupts::new();
+                interrupts.write_flag(data);
+                interrupts.flag()
+            }
+            _ => unreachable!("No device reader for {address:#06x}"),
+        }
+    }
+
+    #[test]
+    fn should_read_every_unused_bit_as_1() {
+        for (address, bits) in UNUSED_IO_BITS {
+            assert_eq!(
+                read_after_write(address, 0b00000000) & bits,
+                bits,
+                "register {address:#06x}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_read_back_all_bits_when_all_bits_are_written() {
+        for (address, _) in UNUSED_IO_BITS {
+            assert_eq!(
+                read_after_write(address, 0b11111111),
+                0b11111111,
+                "register {address:#06x}"
+            );
+        }
+    }
+
+    // P1's input lines idle high, so they read as 1 even though they exist.
+    #[rstest]
+    #[case(P1, 0b11001111)]
+    #[case(SC, 0b01111110)]
+    #[case(TAC, 0b11111000)]
+    #[case(IF, 0b11100000)]
+    fn should_only_read_unused_bits_after_writing_0(#[case] address: u16, #[case] expected: u8) {
+        assert_eq!(read_after_write(address, 0b00000000), expected);
+    }
+
+    #[rstest]
+    #[case(P1, 0b11000000)]
+    #[case(SC, 0b01111110)]
+    #[case(TAC, 0b11111000)]
+    #[case(IF, 0b11100000)]
+    #[case(0xFF01, 0b00000000)]
+    #[case(0xFF05, 0b00000000)]
+    fn should_look_up_unused_bits_by_address(#[case] address: u16, #[case] expected: u8) {
+        assert_eq!(unused_bits(address), expected);
+    }
+}
@@ -0,0 +1,277 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+use crate::{unused_bits, P1};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Joypad {
+    pub interrupt_requested: bool,
+    select: u8,
+    pressed: u8,
+}
+
+impl Joypad {
+    pub fn new() -> Joypad {
+        Joypad {
+            interrupt_requested: false,
+            select: 0b00110000,
+            pressed: 0,
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        unused_bits(P1) | self.select | self.input_lines()
+    }
+
+    pub fn write(&mut self, data: u8) {
+        let lines = self.input_lines();
+        self.select = data & 0b00110000;
+        self.detect_falling_edge(lines);
+    }
+
+    pub fn press(&mut self, button: Button) {
+        let lines = self.input_lines();
+        self.pressed |= Self::button_mask(button);
+        self.detect_falling_edge(lines);
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.pressed &= !Self::button_mask(button);
+    }
+
+    // The d-pad occupies the low nibble and the buttons the high nibble, so that each nibble
+    // lines up with the P1 input lines it drives.
+    fn button_mask(button: Button) -> u8 {
+        match button {
+            Button::Right => 0b00000001,
+            Button::Left => 0b00000010,
+            Button::Up => 0b00000100,
+            Button::Down => 0b00001000,
+            Button::A => 0b00010000,
+            Button::B => 0b00100000,
+            Button::Select => 0b01000000,
+            Button::Start => 0b10000000,
+        }
+    }
+
+    // Input lines are active low, and a group only pulls them low while it is selected.
+    fn input_lines(&self) -> u8 {
+        let mut lines = 0b00001111;
+
+        if self.select & 0b00010000 == 0 {
+            lines &= !self.pressed;
+        }
+
+        if self.select & 0b00100000 == 0 {
+            lines &= !(self.pressed >> 4);
+        }
+
+        lines & 0b00001111
+    }
+
+    fn detect_falling_edge(&mut self, previous_lines: u8) {
+        if previous_lines & !self.input_lines() != 0 {
+            self.interrupt_requested = true;
+        }
+    }
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    #[test]
+    fn should_initialize_to_nothing_selected_or_pressed() {
+        let joypad = Joypad::new();
+        assert_eq!(joypad.read(), 0b11111111);
+        assert!(!joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_initialize_to_nothing_selected_or_pressed_by_default() {
+        let joypad = Joypad::default();
+        assert_eq!(joypad.read(), 0b11111111);
+        assert!(!joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_clone_joypad() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00010000);
+        joypad.press(Button::A);
+
+        assert_eq!(joypad.clone(), joypad);
+    }
+
+    #[rstest]
+    #[case(0b00000000)]
+    #[case(0b00110000)]
+    #[case(0b11111111)]
+    fn should_read_unused_p1_bits_as_1(#[case] data: u8) {
+        let mut joypad = Joypad::new();
+        joypad.write(data);
+        assert_eq!(joypad.read() & 0b11000000, 0b11000000);
+    }
+
+    #[test]
+    fn should_only_store_select_bits() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00001111);
+        assert_eq!(joypad.read(), 0b11001111);
+    }
+
+    #[rstest]
+    #[case(Button::Right, 0b11101110)]
+    #[case(Button::Left, 0b11101101)]
+    #[case(Button::Up, 0b11101011)]
+    #[case(Button::Down, 0b11100111)]
+    fn should_read_pressed_direction_when_d_pad_is_selected(
+        #[case] button: Button,
+        #[case] expected: u8,
+    ) {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00100000);
+        joypad.press(button);
+        assert_eq!(joypad.read(), expected);
+    }
+
+    #[rstest]
+    #[case(Button::A, 0b11011110)]
+    #[case(Button::B, 0b11011101)]
+    #[case(Button::Select, 0b11011011)]
+    #[case(Button::Start, 0b11010111)]
+    fn should_read_pressed_button_when_buttons_are_selected(
+        #[case] button: Button,
+        #[case] expected: u8,
+    ) {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00010000);
+        joypad.press(button);
+        assert_eq!(joypad.read(), expected);
+    }
+
+    #[test]
+    fn should_not_read_buttons_when_only_d_pad_is_selected() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00100000);
+        joypad.press(Button::A);
+        assert_eq!(joypad.read(), 0b11101111);
+    }
+
+    #[test]
+    fn should_combine_both_groups_when_both_are_selected() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00000000);
+        joypad.press(Button::Right);
+        joypad.press(Button::Start);
+        assert_eq!(joypad.read(), 0b11000110);
+    }
+
+    #[test]
+    fn should_read_released_button_as_high() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00010000);
+        joypad.press(Button::A);
+        joypad.release(Button::A);
+        assert_eq!(joypad.read(), 0b11011111);
+    }
+
+    #[test]
+    fn should_request_interrupt_when_selected_button_is_pressed() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00010000);
+        joypad.press(Button::B);
+        assert!(joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_not_request_interrupt_when_unselected_button_is_pressed() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00100000);
+        joypad.press(Button::B);
+        assert!(!joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_not_request_interrupt_when_button_is_released() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00010000);
+        joypad.press(Button::B);
+        joypad.interrupt_requested = false;
+
+        joypad.release(Button::B);
+
+        assert!(!joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_not_request_interrupt_when_line_is_already_low() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00000000);
+        joypad.press(Button::Right);
+        joypad.interrupt_requested = false;
+
+        joypad.press(Button::A);
+
+        assert!(!joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_request_interrupt_when_selecting_group_with_pressed_button() {
+        let mut joypad = Joypad::new();
+        joypad.press(Button::Start);
+        assert!(!joypad.interrupt_requested);
+
+        joypad.write(0b00010000);
+
+        assert!(joypad.interrupt_requested);
+    }
+
+    #[test]
+    fn should_not_request_interrupt_when_deselecting_group_with_pressed_button() {
+        let mut joypad = Joypad::new();
+        joypad.write(0b00010000);
+        joypad.press(Button::Start);
+        joypad.interrupt_requested = false;
+
+        joypad.write(0b00110000);
+
+        assert!(!joypad.interrupt_requested);
+    }
+}
@@ -17,9 +17,28 @@
     with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
 */
 
+mod apu;
+mod build_info;
 mod bus;
 mod cpu;
+mod interrupt;
+mod io;
+mod joypad;
+mod model;
 mod opcode;
+mod serial;
+mod tile;
+mod timer;
 
+pub use apu::*;
+pub use build_info::*;
 pub use bus::*;
 pub use cpu::*;
+pub use interrupt::*;
+pub use io::*;
+pub use joypad::*;
+pub use model::*;
+pub use opcode::{MCycle, Opcode, PrefixOpcode, Register16Bit, Register8Bit};
+pub use serial::*;
+pub use tile::*;
+pub use timer::*;
@@ -27,7 +27,7 @@ use sdl2::{
     Sdl, VideoSubsystem,
 };
 
-use garlicjr::{Bus, ReadWriteMode, SharpSM83};
+use garlicjr::{BuildInfo, Bus, HardwareModel, Opcode, ReadWriteMode, SharpSM83};
 
 // Create a new glow context.
 fn glow_context(window: &Window) -> glow::Context {
@@ -93,6 +93,7 @@ fn main() {
 
     let mut cpu = SharpSM83::new();
     let mut bus = Bus::new();
+    let build_info = BuildInfo::new(HardwareModel::Dmg);
 
     'main: loop {
         for event in event_pump.poll_iter() {
@@ -112,6 +113,7 @@ fn main() {
         /* create imgui UI here */
         cpu_window(ui, &mut cpu, &mut bus);
         bus_window(ui, &mut bus);
+        about_window(ui, &build_info);
 
         /* render */
         let draw_data = imgui.render();
@@ -130,30 +132,48 @@ fn cpu_window(ui: &imgui::Ui, cpu: &mut SharpSM83, bus: &mut Bus) {
 }
 
 fn input_cpu(ui: &imgui::Ui, cpu: &mut SharpSM83, bus: &mut Bus) {
-    let mut af = [cpu.registers.a, cpu.registers.f];
-    let mut bc = [cpu.registers.b, cpu.registers.c];
-    let mut de = [cpu.registers.d, cpu.registers.e];
-    let mut hl = [cpu.registers.h, cpu.registers.l];
-
-    Drag::new("AF").build_array(ui, &mut af);
-    Drag::new("BC").build_array(ui, &mut bc);
-    Drag::new("DE").build_array(ui, &mut de);
-    Drag::new("HL").build_array(ui, &mut hl);
+    let mut af = cpu.registers.af();
+    let mut bc = cpu.registers.bc();
+    let mut de = cpu.registers.de();
+    let mut hl = cpu.registers.hl();
+
+    Drag::new("AF").build(ui, &mut af);
+    Drag::new("BC").build(ui, &mut bc);
+    Drag::new("DE").build(ui, &mut de);
+    Drag::new("HL").build(ui, &mut hl);
     Drag::new("SP").build(ui, &mut cpu.registers.stack_pointer);
     Drag::new("PC").build(ui, &mut cpu.registers.program_counter);
 
-    cpu.registers.a = af[0];
-    cpu.registers.f = af[1];
-    cpu.registers.b = bc[0];
-    cpu.registers.c = bc[1];
-    cpu.registers.d = de[0];
-    cpu.registers.e = de[1];
-    cpu.registers.h = hl[0];
-    cpu.registers.l = hl[1];
+    cpu.registers.set_af(af);
+    cpu.registers.set_bc(bc);
+    cpu.registers.set_de(de);
+    cpu.registers.set_hl(hl);
+
+    let mut flags = cpu.registers.flags();
+    let flags_changed = ui.checkbox("Z", &mut flags.z)
+        | ui.checkbox("N", &mut flags.n)
+        | ui.checkbox("H", &mut flags.h)
+        | ui.checkbox("C", &mut flags.c);
+
+    if flags_changed {
+        cpu.registers.set_flags(flags);
+    }
 
     if ui.button("Step") {
         cpu.tick(bus);
     }
+
+    explain_opcode(ui, cpu.opcode());
+}
+
+fn explain_opcode(ui: &imgui::Ui, opcode: Opcode) {
+    ui.separator();
+    ui.text(format!("{opcode:?}"));
+
+    match opcode.explain() {
+        Some(explanation) => explanation.iter().for_each(|line| ui.text(line)),
+        None => ui.text("Not implemented"),
+    }
 }
 
 fn bus_window(ui: &imgui::Ui, bus: &mut Bus) {
@@ -168,3 +188,29 @@ fn input_bus(ui: &imgui::Ui, bus: &mut Bus) {
     ui.radio_button("read", &mut bus.mode, ReadWriteMode::Read);
     ui.radio_button("write", &mut bus.mode, ReadWriteMode::Write);
 }
+
+fn about_window(ui: &imgui::Ui, build_info: &BuildInfo) {
+    ui.window("About")
+        .size([200.0, 100.0], imgui::Condition::FirstUseEver)
+        .build(|| show_build_info(ui, build_info));
+}
+
+fn show_build_info(ui: &imgui::Ui, build_info: &BuildInfo) {
+    ui.text(format!("garlicjr {}", build_info.version));
+
+    if build_info.features.is_empty() {
+        ui.text("Features: none");
+    } else {
+        ui.text(format!("Features: {}", build_info.features.join(", ")));
+    }
+
+    ui.text(format!("Model: {:?}", build_info.model));
+    ui.text(format!(
+        "High-pass charge factor: {}",
+        build_info.quirks.high_pass_charge_factor
+    ));
+    ui.text(format!(
+        "Fast serial clock: {}",
+        build_info.quirks.serial_fast_clock
+    ));
+}
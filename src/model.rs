@@ -0,0 +1,95 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum HardwareModel {
+    Dmg,
+    Mgb,
+    Cgb,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct QuirkSet {
+    pub high_pass_charge_factor: f32,
+    pub serial_fast_clock: bool,
+}
+
+impl HardwareModel {
+    pub fn quirks(&self) -> QuirkSet {
+        QuirkSet::for_model(*self)
+    }
+}
+
+impl QuirkSet {
+    pub fn for_model(model: HardwareModel) -> QuirkSet {
+        match model {
+            HardwareModel::Dmg => QuirkSet {
+                high_pass_charge_factor: 0.999958,
+                serial_fast_clock: false,
+            },
+            HardwareModel::Mgb => QuirkSet {
+                high_pass_charge_factor: 0.998943,
+                serial_fast_clock: false,
+            },
+            HardwareModel::Cgb => QuirkSet {
+                high_pass_charge_factor: 0.998943,
+                serial_fast_clock: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case(HardwareModel::Dmg, 0.999958)]
+    #[case(HardwareModel::Mgb, 0.998943)]
+    #[case(HardwareModel::Cgb, 0.998943)]
+    fn should_use_model_high_pass_charge_factor(
+        #[case] model: HardwareModel,
+        #[case] expected: f32,
+    ) {
+        let quirks = QuirkSet::for_model(model);
+        assert_eq!(quirks.high_pass_charge_factor, expected);
+    }
+
+    #[rstest]
+    #[case(HardwareModel::Dmg, false)]
+    #[case(HardwareModel::Mgb, false)]
+    #[case(HardwareModel::Cgb, true)]
+    fn should_only_support_fast_serial_clock_on_cgb(
+        #[case] model: HardwareModel,
+        #[case] expected: bool,
+    ) {
+        let quirks = QuirkSet::for_model(model);
+        assert_eq!(quirks.serial_fast_clock, expected);
+    }
+
+    #[rstest]
+    #[case(HardwareModel::Dmg)]
+    #[case(HardwareModel::Mgb)]
+    #[case(HardwareModel::Cgb)]
+    fn should_return_quirks_for_model(#[case] model: HardwareModel) {
+        assert_eq!(model.quirks(), QuirkSet::for_model(model));
+    }
+}
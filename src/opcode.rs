@@ -17,8 +17,7 @@
     with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
 */
 
-#[derive(Debug, PartialEq)]
-#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Opcode {
     Nop,
     LdReg8Imm8(Register8Bit),
@@ -39,12 +38,70 @@ pub enum Opcode {
     LdImm16AddrSp,
     Halt,
     Stop,
+    Prefix,
     Unimplemented(u8),
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MCycle {
+    FetchOpcode,
+    ReadImm8(Register8Bit),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PrefixOpcode {
+    Rlc(Register8Bit),
+    Rrc(Register8Bit),
+    Rl(Register8Bit),
+    Rr(Register8Bit),
+    Sla(Register8Bit),
+    Sra(Register8Bit),
+    Swap(Register8Bit),
+    Srl(Register8Bit),
+    Bit(u8, Register8Bit),
+    Res(u8, Register8Bit),
+    Set(u8, Register8Bit),
+}
+
+// Encodings outside the load group that the decoder handles. Every LD r, r' and HALT encoding in
+// 0x40..=0x7F is handled as well.
+const DECODED_OPCODES: [u8; 24] = [
+    0x00, 0x01, 0x02, 0x06, 0x08, 0x0A, 0x0E, 0x10, 0x11, 0x12, 0x16, 0x1A, 0x1E, 0x21, 0x22, 0x26,
+    0x2A, 0x2E, 0x31, 0x32, 0x36, 0x3A, 0x3E, 0xCB,
+];
+
+// Checked at compile time so that a decoder change can't silently turn a handled opcode into
+// Unimplemented.
+const _: () = {
+    let mut i = 0;
+    while i < DECODED_OPCODES.len() {
+        assert!(!matches!(
+            Opcode::decode(DECODED_OPCODES[i]),
+            Opcode::Unimplemented(_)
+        ));
+        i += 1;
+    }
+
+    let mut data = 0x40;
+    while data < 0x80 {
+        assert!(!matches!(Opcode::decode(data), Opcode::Unimplemented(_)));
+        data += 1;
+    }
+};
+
+impl MCycle {
+    pub fn describe(&self) -> String {
+        match self {
+            MCycle::FetchOpcode => String::from("read opcode from [PC], PC += 1"),
+            MCycle::ReadImm8(destination) => {
+                format!("read n8 from [PC] into {destination:?}, PC += 1, no flags affected")
+            }
+        }
+    }
+}
+
 impl Opcode {
-    #[allow(dead_code)]
-    pub fn decode(data: u8) -> Opcode {
+    pub const fn decode(data: u8) -> Opcode {
         if data == 0b00000000 {
             return Opcode::Nop;
         }
@@ -61,6 +118,10 @@ impl Opcode {
             return Opcode::LdImm16AddrSp;
         }
 
+        if data == 0b11001011 {
+            return Opcode::Prefix;
+        }
+
         let opcode = Self::decode_top_2(data);
         if let Some(opcode) = opcode {
             return opcode;
@@ -71,11 +132,80 @@ impl Opcode {
             return opcode;
         }
 
-        let opcode = Self::decode_top_2_bottom_4(data);
-        opcode.unwrap_or(Opcode::Unimplemented(data))
+        match Self::decode_top_2_bottom_4(data) {
+            Some(opcode) => opcode,
+            None => Opcode::Unimplemented(data),
+        }
+    }
+
+    // Every opcode after the 0xCB prefix is defined, so prefixed decoding can't fail.
+    pub const fn decode_as_prefix(data: u8) -> PrefixOpcode {
+        let register = Register8Bit::from_u8(data & 0b00000111);
+        let bit = (data & 0b00111000) >> 3;
+
+        match data & 0b11000000 {
+            0b00000000 => match bit {
+                0 => PrefixOpcode::Rlc(register),
+                1 => PrefixOpcode::Rrc(register),
+                2 => PrefixOpcode::Rl(register),
+                3 => PrefixOpcode::Rr(register),
+                4 => PrefixOpcode::Sla(register),
+                5 => PrefixOpcode::Sra(register),
+                6 => PrefixOpcode::Swap(register),
+                _ => PrefixOpcode::Srl(register),
+            },
+            0b01000000 => PrefixOpcode::Bit(bit, register),
+            0b10000000 => PrefixOpcode::Res(bit, register),
+            _ => PrefixOpcode::Set(bit, register),
+        }
     }
 
-    fn decode_top_2(data: u8) -> Option<Opcode> {
+    // The length of an opcode that isn't decoded yet is unknown.
+    pub const fn length(&self) -> Option<u8> {
+        match self {
+            Opcode::LdReg8Imm8(_) | Opcode::LdHlAddrImm8 | Opcode::Stop | Opcode::Prefix => Some(2),
+            Opcode::LdReg16Imm16(_) | Opcode::LdImm16AddrSp => Some(3),
+            Opcode::Nop
+            | Opcode::LdReg8Reg8 { .. }
+            | Opcode::LdReg8HlAddr(_)
+            | Opcode::LdAReg16Addr(_)
+            | Opcode::LdAHliAddr
+            | Opcode::LdAHldAddr
+            | Opcode::LdHlAddrReg8(_)
+            | Opcode::LdReg16AddrA(_)
+            | Opcode::LdHliAddrA
+            | Opcode::LdHldAddrA
+            | Opcode::Halt => Some(1),
+            Opcode::Unimplemented(_) => None,
+        }
+    }
+
+    // The CPU steps through these M-cycles to execute the opcode. There are none past the last
+    // M-cycle, and none at all for opcodes the CPU does not execute yet.
+    pub const fn m_cycle(&self, index: u8) -> Option<MCycle> {
+        match (self, index) {
+            (Opcode::Nop | Opcode::LdReg8Imm8(_), 0) => Some(MCycle::FetchOpcode),
+            (Opcode::LdReg8Imm8(destination), 1) => Some(MCycle::ReadImm8(*destination)),
+            _ => None,
+        }
+    }
+
+    // Built from the same M-cycles the CPU executes, so the explanation can't drift from it.
+    pub fn explain(&self) -> Option<Vec<String>> {
+        let explanation: Vec<String> = (0..)
+            .map_while(|index| self.m_cycle(index))
+            .enumerate()
+            .map(|(index, m_cycle)| format!("M{}: {}", index + 1, m_cycle.describe()))
+            .collect();
+
+        if explanation.is_empty() {
+            None
+        } else {
+            Some(explanation)
+        }
+    }
+
+    const fn decode_top_2(data: u8) -> Option<Opcode> {
         let top_2 = data & 0b11000000;
 
         match top_2 {
@@ -88,15 +218,15 @@ impl Opcode {
 
                 // This is a special case because LD [HL], [HL] results in 01110110, which is the
                 // HALT opcode.
-                if source == Register8Bit::HLAddr && destination == Register8Bit::HLAddr {
+                if source.is_hl_addr() && destination.is_hl_addr() {
                     return Some(Opcode::Halt);
                 }
 
-                if source == Register8Bit::HLAddr {
+                if source.is_hl_addr() {
                     return Some(Opcode::LdReg8HlAddr(destination));
                 }
 
-                if destination == Register8Bit::HLAddr {
+                if destination.is_hl_addr() {
                     return Some(Opcode::LdHlAddrReg8(source));
                 }
 
@@ -109,7 +239,7 @@ impl Opcode {
         }
     }
 
-    fn decode_top_2_bottom_3(data: u8) -> Option<Opcode> {
+    const fn decode_top_2_bottom_3(data: u8) -> Option<Opcode> {
         let top_2 = data & 0b11000000;
         let bot_3 = data & 0b00000111;
 
@@ -117,7 +247,7 @@ impl Opcode {
             (0b00000000, 0b00000110) => {
                 let reg_num = (data & 0b00111000) >> 3;
                 let register = Register8Bit::from_u8(reg_num);
-                if register == Register8Bit::HLAddr {
+                if register.is_hl_addr() {
                     Some(Opcode::LdHlAddrImm8)
                 } else {
                     Some(Opcode::LdReg8Imm8(register))
@@ -127,7 +257,7 @@ impl Opcode {
         }
     }
 
-    fn decode_top_2_bottom_4(data: u8) -> Option<Opcode> {
+    const fn decode_top_2_bottom_4(data: u8) -> Option<Opcode> {
         let top_2 = data & 0b11000000;
         let bot_4 = data & 0b00001111;
 
@@ -176,7 +306,7 @@ pub enum Register8Bit {
 }
 
 impl Register8Bit {
-    pub fn from_u8(data: u8) -> Register8Bit {
+    pub(crate) const fn from_u8(data: u8) -> Register8Bit {
         match data {
             0 => Register8Bit::B,
             1 => Register8Bit::C,
@@ -189,6 +319,10 @@ impl Register8Bit {
             _ => panic!("Invalid register"),
         }
     }
+
+    const fn is_hl_addr(&self) -> bool {
+        matches!(self, Register8Bit::HLAddr)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -200,7 +334,7 @@ pub enum Register16Bit {
 }
 
 impl Register16Bit {
-    pub fn from_u8(data: u8) -> Register16Bit {
+    pub(crate) const fn from_u8(data: u8) -> Register16Bit {
         match data {
             0 => Register16Bit::BC,
             1 => Register16Bit::DE,
@@ -220,7 +354,7 @@ pub enum Register16BitMemory {
 }
 
 impl Register16BitMemory {
-    pub fn from_u8(data: u8) -> Register16BitMemory {
+    pub(crate) const fn from_u8(data: u8) -> Register16BitMemory {
         match data {
             0 => Register16BitMemory::BC,
             1 => Register16BitMemory::DE,
@@ -260,6 +394,119 @@ mod tests {
         assert_eq!(opcode, Opcode::Unimplemented(code));
     }
 
+    #[test]
+    fn should_decode_every_byte_without_panicking() {
+        for data in 0..=u8::MAX {
+            Opcode::decode(data);
+        }
+    }
+
+    #[test]
+    fn should_decode_in_const_context() {
+        const OPCODE: Opcode = Opcode::decode(0b00000110);
+        assert_eq!(OPCODE, Opcode::LdReg8Imm8(Register8Bit::B));
+    }
+
+    #[rstest]
+    #[case(0b00000000, Some(1))]
+    #[case(0b00111110, Some(2))]
+    #[case(0b01111000, Some(1))]
+    #[case(0b01111110, Some(1))]
+    #[case(0b00001010, Some(1))]
+    #[case(0b00101010, Some(1))]
+    #[case(0b00110110, Some(2))]
+    #[case(0b00000001, Some(3))]
+    #[case(0b01110111, Some(1))]
+    #[case(0b00000010, Some(1))]
+    #[case(0b00100010, Some(1))]
+    #[case(0b00001000, Some(3))]
+    #[case(0b01110110, Some(1))]
+    #[case(0b00010000, Some(2))]
+    #[case(0b11001011, Some(2))]
+    #[case(0xD3, None)]
+    #[case(0xC3, None)]
+    #[case(0xCD, None)]
+    fn should_return_instruction_length(#[case] raw_opcode: u8, #[case] expected: Option<u8>) {
+        let opcode = Opcode::decode(raw_opcode);
+        assert_eq!(opcode.length(), expected);
+    }
+
+    #[test]
+    fn should_return_prefix_given_0xcb() {
+        assert_eq!(Opcode::decode(0xCB), Opcode::Prefix);
+    }
+
+    #[rstest]
+    #[case(0x00, PrefixOpcode::Rlc(Register8Bit::B))]
+    #[case(0x0F, PrefixOpcode::Rrc(Register8Bit::A))]
+    #[case(0x11, PrefixOpcode::Rl(Register8Bit::C))]
+    #[case(0x1E, PrefixOpcode::Rr(Register8Bit::HLAddr))]
+    #[case(0x22, PrefixOpcode::Sla(Register8Bit::D))]
+    #[case(0x2B, PrefixOpcode::Sra(Register8Bit::E))]
+    #[case(0x37, PrefixOpcode::Swap(Register8Bit::A))]
+    #[case(0x3D, PrefixOpcode::Srl(Register8Bit::L))]
+    #[case(0x40, PrefixOpcode::Bit(0, Register8Bit::B))]
+    #[case(0x7C, PrefixOpcode::Bit(7, Register8Bit::H))]
+    #[case(0x86, PrefixOpcode::Res(0, Register8Bit::HLAddr))]
+    #[case(0xBF, PrefixOpcode::Res(7, Register8Bit::A))]
+    #[case(0xC7, PrefixOpcode::Set(0, Register8Bit::A))]
+    #[case(0xFE, PrefixOpcode::Set(7, Register8Bit::HLAddr))]
+    fn should_decode_prefixed_opcode(#[case] data: u8, #[case] expected: PrefixOpcode) {
+        assert_eq!(Opcode::decode_as_prefix(data), expected);
+    }
+
+    #[test]
+    fn should_decode_every_prefixed_byte_without_panicking() {
+        for data in 0..=u8::MAX {
+            Opcode::decode_as_prefix(data);
+        }
+    }
+
+    #[test]
+    fn should_explain_nop_as_a_single_fetch() {
+        let explanation = Opcode::Nop.explain();
+        assert_eq!(
+            explanation,
+            Some(vec![String::from("M1: read opcode from [PC], PC += 1")])
+        );
+    }
+
+    #[rstest]
+    #[case(Register8Bit::A)]
+    #[case(Register8Bit::B)]
+    #[case(Register8Bit::L)]
+    fn should_explain_ld_r_n8_destination(#[case] destination: Register8Bit) {
+        let explanation = Opcode::LdReg8Imm8(destination).explain().unwrap();
+        assert_eq!(explanation.len(), 2);
+        assert!(explanation[1].contains(&format!("into {destination:?}")));
+    }
+
+    #[rstest]
+    #[case(Opcode::Nop, 1)]
+    #[case(Opcode::LdReg8Imm8(Register8Bit::C), 2)]
+    #[case(Opcode::Halt, 0)]
+    #[case(Opcode::Unimplemented(0xD3), 0)]
+    fn should_end_m_cycles_after_last_m_cycle(#[case] opcode: Opcode, #[case] count: u8) {
+        for index in 0..count {
+            assert!(opcode.m_cycle(index).is_some());
+        }
+
+        assert_eq!(opcode.m_cycle(count), None);
+    }
+
+    #[test]
+    fn should_read_immediate_into_destination_in_second_m_cycle() {
+        let opcode = Opcode::LdReg8Imm8(Register8Bit::E);
+        assert_eq!(opcode.m_cycle(0), Some(MCycle::FetchOpcode));
+        assert_eq!(opcode.m_cycle(1), Some(MCycle::ReadImm8(Register8Bit::E)));
+    }
+
+    #[test]
+    fn should_not_explain_unimplemented_opcode() {
+        assert_eq!(Opcode::Halt.explain(), None);
+        assert_eq!(Opcode::Unimplemented(0xD3).explain(), None);
+    }
+
     #[test]
     fn should_return_nop_when_data_is_0b00000000() {
         let opcode = Opcode::decode(0b00000000);
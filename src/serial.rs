@@ -0,0 +1,260 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+use crate::{unused_bits, QuirkSet, SC};
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Serial {
+    pub sb: u8,
+    pub interrupt_requested: bool,
+    sc: u8,
+    fast_clock_supported: bool,
+    bits_remaining: u8,
+    cycles: u16,
+}
+
+impl Serial {
+    const NORMAL_CLOCK_PERIOD: u16 = 512;
+    const FAST_CLOCK_PERIOD: u16 = 16;
+
+    pub fn new(quirks: &QuirkSet) -> Serial {
+        Serial {
+            sb: 0,
+            interrupt_requested: false,
+            sc: 0,
+            fast_clock_supported: quirks.serial_fast_clock,
+            bits_remaining: 0,
+            cycles: 0,
+        }
+    }
+
+    // The clock speed bit only exists on CGB.
+    pub fn sc(&self) -> u8 {
+        if self.fast_clock_supported {
+            unused_bits(SC) & !0b00000010 | self.sc
+        } else {
+            unused_bits(SC) | self.sc
+        }
+    }
+
+    pub fn write_sc(&mut self, data: u8) {
+        self.sc = if self.fast_clock_supported {
+            data & 0b10000011
+        } else {
+            data & 0b10000001
+        };
+
+        if self.transferring() {
+            self.bits_remaining = 8;
+            self.cycles = 0;
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if !self.transferring() || !self.internal_clock() {
+            return;
+        }
+
+        self.cycles += 1;
+        if self.cycles < self.clock_period() {
+            return;
+        }
+
+        self.cycles = 0;
+        self.shift_bit();
+    }
+
+    fn transferring(&self) -> bool {
+        self.sc & 0b10000000 != 0
+    }
+
+    fn internal_clock(&self) -> bool {
+        self.sc & 0b00000001 != 0
+    }
+
+    fn clock_period(&self) -> u16 {
+        if self.sc & 0b00000010 != 0 {
+            Self::FAST_CLOCK_PERIOD
+        } else {
+            Self::NORMAL_CLOCK_PERIOD
+        }
+    }
+
+    // The serial line is open-drain, so with nothing connected every bit shifted in reads as 1.
+    fn shift_bit(&mut self) {
+        self.sb = self.sb << 1 | 1;
+        self.bits_remaining -= 1;
+
+        if self.bits_remaining == 0 {
+            self.sc &= !0b10000000;
+            self.interrupt_requested = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    use crate::HardwareModel;
+
+    fn tick_times(serial: &mut Serial, cycles: u32) {
+        for _ in 0..cycles {
+            serial.tick();
+        }
+    }
+
+    #[test]
+    fn should_initialize_registers_to_0() {
+        let serial = Serial::new(&HardwareModel::Dmg.quirks());
+        assert_eq!(serial.sb, 0);
+        assert_eq!(serial.sc(), 0b01111110);
+        assert!(!serial.interrupt_requested);
+    }
+
+    #[test]
+    fn should_clone_serial_mid_transfer() {
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        serial.write_sc(0b10000001);
+        tick_times(&mut serial, 2048);
+
+        let mut clone = serial.clone();
+        assert_eq!(clone, serial);
+
+        tick_times(&mut serial, 2048);
+        tick_times(&mut clone, 2048);
+
+        assert_eq!(clone, serial);
+        assert!(clone.interrupt_requested);
+    }
+
+    #[rstest]
+    #[case(HardwareModel::Dmg, 0b00000000, 0b01111110)]
+    #[case(HardwareModel::Dmg, 0b11111111, 0b11111111)]
+    #[case(HardwareModel::Dmg, 0b00000011, 0b01111111)]
+    #[case(HardwareModel::Cgb, 0b00000000, 0b01111100)]
+    #[case(HardwareModel::Cgb, 0b00000010, 0b01111110)]
+    #[case(HardwareModel::Cgb, 0b01111111, 0b01111111)]
+    fn should_read_unused_sc_bits_as_1(
+        #[case] model: HardwareModel,
+        #[case] data: u8,
+        #[case] expected: u8,
+    ) {
+        let mut serial = Serial::new(&model.quirks());
+        serial.write_sc(data);
+        assert_eq!(serial.sc(), expected);
+    }
+
+    #[rstest]
+    #[case(HardwareModel::Dmg, 0b10000001, 4096)]
+    #[case(HardwareModel::Dmg, 0b10000011, 4096)]
+    #[case(HardwareModel::Cgb, 0b10000001, 4096)]
+    #[case(HardwareModel::Cgb, 0b10000011, 128)]
+    fn should_complete_transfer_after_8_clock_periods(
+        #[case] model: HardwareModel,
+        #[case] sc: u8,
+        #[case] duration: u32,
+    ) {
+        let mut serial = Serial::new(&model.quirks());
+        serial.write_sc(sc);
+
+        tick_times(&mut serial, duration - 1);
+        assert_eq!(serial.sc() & 0b10000000, 0b10000000);
+        assert!(!serial.interrupt_requested);
+
+        serial.tick();
+        assert_eq!(serial.sc() & 0b10000000, 0);
+        assert!(serial.interrupt_requested);
+    }
+
+    #[rstest]
+    #[case(HardwareModel::Dmg, 0b10000001, 512)]
+    #[case(HardwareModel::Cgb, 0b10000011, 16)]
+    fn should_shift_one_bit_per_clock_period(
+        #[case] model: HardwareModel,
+        #[case] sc: u8,
+        #[case] period: u32,
+    ) {
+        let mut serial = Serial::new(&model.quirks());
+        serial.sb = 0b00000000;
+        serial.write_sc(sc);
+
+        tick_times(&mut serial, period - 1);
+        assert_eq!(serial.sb, 0b00000000);
+
+        serial.tick();
+        assert_eq!(serial.sb, 0b00000001);
+
+        tick_times(&mut serial, period);
+        assert_eq!(serial.sb, 0b00000011);
+    }
+
+    #[test]
+    fn should_receive_0xff_when_nothing_is_connected() {
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        serial.sb = 0x42;
+        serial.write_sc(0b10000001);
+
+        tick_times(&mut serial, 4096);
+
+        assert_eq!(serial.sb, 0xFF);
+    }
+
+    #[test]
+    fn should_not_transfer_without_start_bit() {
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        serial.sb = 0x42;
+        serial.write_sc(0b00000001);
+
+        tick_times(&mut serial, 4096);
+
+        assert_eq!(serial.sb, 0x42);
+        assert!(!serial.interrupt_requested);
+    }
+
+    #[test]
+    fn should_wait_for_external_clock() {
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        serial.sb = 0x42;
+        serial.write_sc(0b10000000);
+
+        tick_times(&mut serial, 8192);
+
+        assert_eq!(serial.sb, 0x42);
+        assert_eq!(serial.sc() & 0b10000000, 0b10000000);
+        assert!(!serial.interrupt_requested);
+    }
+
+    #[test]
+    fn should_restart_transfer_when_sc_is_written_again() {
+        let mut serial = Serial::new(&HardwareModel::Dmg.quirks());
+        serial.write_sc(0b10000001);
+
+        tick_times(&mut serial, 2048);
+        serial.write_sc(0b10000001);
+        tick_times(&mut serial, 4095);
+
+        assert!(!serial.interrupt_requested);
+
+        serial.tick();
+        assert!(serial.interrupt_requested);
+    }
+}
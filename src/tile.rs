@@ -0,0 +1,342 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+pub const VRAM_SIZE: usize = 0x2000;
+pub const TILE_MAP_WIDTH: usize = 32;
+pub const TILE_MAP_PIXEL_WIDTH: usize = TILE_MAP_WIDTH * 8;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TileMapLayer {
+    Background,
+    Window,
+}
+
+pub fn decode_tile(data: &[u8; 16]) -> [[u8; 8]; 8] {
+    let mut pixels = [[0; 8]; 8];
+
+    for (y, row) in pixels.iter_mut().enumerate() {
+        let low = data[y * 2];
+        let high = data[y * 2 + 1];
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let bit = 7 - x;
+            *pixel = ((high >> bit) & 1) << 1 | ((low >> bit) & 1);
+        }
+    }
+
+    pixels
+}
+
+// LCDC bit 4 selects between unsigned indexing from 0x8000 and signed indexing from 0x9000.
+pub fn tile_data_address(tile_index: u8, lcdc: u8) -> u16 {
+    if lcdc & 0b00010000 != 0 {
+        0x8000 + tile_index as u16 * 16
+    } else {
+        0x9000u16.wrapping_add_signed(tile_index as i8 as i16 * 16)
+    }
+}
+
+// LCDC bit 3 selects the background map and bit 6 selects the window map.
+pub fn tile_map_address(lcdc: u8, layer: TileMapLayer) -> u16 {
+    let select_bit = match layer {
+        TileMapLayer::Background => 0b00001000,
+        TileMapLayer::Window => 0b01000000,
+    };
+
+    if lcdc & select_bit != 0 {
+        0x9C00
+    } else {
+        0x9800
+    }
+}
+
+// LCDC bit 2 selects 8x16 sprites.
+pub fn sprite_height(lcdc: u8) -> u8 {
+    if lcdc & 0b00000100 != 0 {
+        16
+    } else {
+        8
+    }
+}
+
+// Sprites always use unsigned addressing from 0x8000. In 8x16 mode bit 0 of the tile index is
+// ignored, so the top half is the even tile and the bottom half is the odd tile after it. Rows
+// outside the sprite wrap around so that they never address a neighbouring tile.
+pub fn sprite_row_address(tile_index: u8, attributes: u8, row: u8, lcdc: u8) -> u16 {
+    let height = sprite_height(lcdc);
+    let row = row & (height - 1);
+
+    let tile_index = if height == 16 {
+        tile_index & 0b11111110
+    } else {
+        tile_index
+    };
+
+    let row = if attributes & 0b01000000 != 0 {
+        height - 1 - row
+    } else {
+        row
+    };
+
+    0x8000 + tile_index as u16 * 16 + row as u16 * 2
+}
+
+pub fn decode_tile_map(
+    vram: &[u8; VRAM_SIZE],
+    lcdc: u8,
+    layer: TileMapLayer,
+) -> [u8; TILE_MAP_WIDTH * TILE_MAP_WIDTH] {
+    let start = vram_offset(tile_map_address(lcdc, layer));
+
+    let mut indices = [0; TILE_MAP_WIDTH * TILE_MAP_WIDTH];
+    indices.copy_from_slice(&vram[start..start + TILE_MAP_WIDTH * TILE_MAP_WIDTH]);
+    indices
+}
+
+pub fn render_tile_map(
+    vram: &[u8; VRAM_SIZE],
+    lcdc: u8,
+    layer: TileMapLayer,
+    pixels: &mut [u8; TILE_MAP_PIXEL_WIDTH * TILE_MAP_PIXEL_WIDTH],
+) {
+    let indices = decode_tile_map(vram, lcdc, layer);
+
+    for (map_index, tile_index) in indices.iter().enumerate() {
+        let start = vram_offset(tile_data_address(*tile_index, lcdc));
+        let tile = decode_tile(vram[start..start + 16].try_into().unwrap());
+
+        let tile_x = map_index % TILE_MAP_WIDTH * 8;
+        let tile_y = map_index / TILE_MAP_WIDTH * 8;
+
+        for (y, row) in tile.iter().enumerate() {
+            let offset = (tile_y + y) * TILE_MAP_PIXEL_WIDTH + tile_x;
+            pixels[offset..offset + 8].copy_from_slice(row);
+        }
+    }
+}
+
+fn vram_offset(address: u16) -> usize {
+    (address - 0x8000) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    #[test]
+    fn should_decode_tile_rows_from_bitplanes() {
+        let mut data = [0; 16];
+        data[0] = 0b10101010;
+        data[1] = 0b11001100;
+
+        let tile = decode_tile(&data);
+
+        assert_eq!(tile[0], [3, 2, 1, 0, 3, 2, 1, 0]);
+        assert_eq!(tile[1], [0; 8]);
+    }
+
+    #[test]
+    fn should_decode_every_row_of_tile() {
+        let data = [
+            0x3C, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x5E, 0x7E, 0x0A, 0x7C, 0x56,
+            0x38, 0x7C,
+        ];
+
+        let tile = decode_tile(&data);
+
+        assert_eq!(tile[0], [0, 2, 3, 3, 3, 3, 2, 0]);
+        assert_eq!(tile[1], [0, 3, 0, 0, 0, 0, 3, 0]);
+        assert_eq!(tile[4], [0, 3, 1, 3, 3, 3, 3, 0]);
+        assert_eq!(tile[7], [0, 2, 3, 3, 3, 2, 0, 0]);
+    }
+
+    #[rstest]
+    #[case(0x00, 0x8000)]
+    #[case(0x01, 0x8010)]
+    #[case(0x7F, 0x87F0)]
+    #[case(0x80, 0x8800)]
+    #[case(0xFF, 0x8FF0)]
+    fn should_use_unsigned_addressing_when_lcdc_bit_4_is_set(
+        #[case] tile_index: u8,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(tile_data_address(tile_index, 0b00010000), expected);
+    }
+
+    #[rstest]
+    #[case(0x00, 0x9000)]
+    #[case(0x01, 0x9010)]
+    #[case(0x7F, 0x97F0)]
+    #[case(0x80, 0x8800)]
+    #[case(0xFF, 0x8FF0)]
+    fn should_use_signed_addressing_when_lcdc_bit_4_is_clear(
+        #[case] tile_index: u8,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(tile_data_address(tile_index, 0b00000000), expected);
+    }
+
+    #[rstest]
+    #[case(0b00000000, TileMapLayer::Background, 0x9800)]
+    #[case(0b00001000, TileMapLayer::Background, 0x9C00)]
+    #[case(0b01000000, TileMapLayer::Background, 0x9800)]
+    #[case(0b00000000, TileMapLayer::Window, 0x9800)]
+    #[case(0b01000000, TileMapLayer::Window, 0x9C00)]
+    #[case(0b00001000, TileMapLayer::Window, 0x9800)]
+    fn should_select_tile_map_from_lcdc(
+        #[case] lcdc: u8,
+        #[case] layer: TileMapLayer,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(tile_map_address(lcdc, layer), expected);
+    }
+
+    #[rstest]
+    #[case(0b00000000, 8)]
+    #[case(0b00000100, 16)]
+    #[case(0b11111011, 8)]
+    fn should_select_sprite_height_from_lcdc(#[case] lcdc: u8, #[case] expected: u8) {
+        assert_eq!(sprite_height(lcdc), expected);
+    }
+
+    #[rstest]
+    #[case(0x42, 0b00000000, 0, 0x8420)]
+    #[case(0x42, 0b00000000, 7, 0x842E)]
+    #[case(0x43, 0b00000000, 0, 0x8430)]
+    #[case(0x42, 0b01000000, 0, 0x842E)]
+    #[case(0x42, 0b01000000, 7, 0x8420)]
+    fn should_address_8x8_sprite_rows(
+        #[case] tile_index: u8,
+        #[case] attributes: u8,
+        #[case] row: u8,
+        #[case] expected: u16,
+    ) {
+        let address = sprite_row_address(tile_index, attributes, row, 0b00000000);
+        assert_eq!(address, expected);
+    }
+
+    #[rstest]
+    #[case(0x42, 0b00000000, 0, 0x8420)]
+    #[case(0x42, 0b00000000, 7, 0x842E)]
+    #[case(0x42, 0b00000000, 8, 0x8430)]
+    #[case(0x42, 0b00000000, 15, 0x843E)]
+    #[case(0x43, 0b00000000, 0, 0x8420)]
+    #[case(0x43, 0b00000000, 8, 0x8430)]
+    fn should_ignore_bit_0_of_tile_index_for_8x16_sprites(
+        #[case] tile_index: u8,
+        #[case] attributes: u8,
+        #[case] row: u8,
+        #[case] expected: u16,
+    ) {
+        let address = sprite_row_address(tile_index, attributes, row, 0b00000100);
+        assert_eq!(address, expected);
+    }
+
+    #[rstest]
+    #[case(0x42, 0b01000000, 0, 0x843E)]
+    #[case(0x42, 0b01000000, 7, 0x8430)]
+    #[case(0x42, 0b01000000, 8, 0x842E)]
+    #[case(0x43, 0b01000000, 15, 0x8420)]
+    fn should_swap_top_and_bottom_tiles_of_y_flipped_8x16_sprites(
+        #[case] tile_index: u8,
+        #[case] attributes: u8,
+        #[case] row: u8,
+        #[case] expected: u16,
+    ) {
+        let address = sprite_row_address(tile_index, attributes, row, 0b00000100);
+        assert_eq!(address, expected);
+    }
+
+    #[rstest]
+    #[case(0b00000000, 8, 0b00000000, 0x8420)]
+    #[case(0b00000000, 15, 0b01000000, 0x8420)]
+    #[case(0b00000100, 16, 0b00000000, 0x8420)]
+    #[case(0b00000100, 31, 0b01000000, 0x8420)]
+    #[case(0b00000100, 255, 0b00000000, 0x843E)]
+    fn should_wrap_rows_outside_of_sprite(
+        #[case] lcdc: u8,
+        #[case] row: u8,
+        #[case] attributes: u8,
+        #[case] expected: u16,
+    ) {
+        assert_eq!(sprite_row_address(0x42, attributes, row, lcdc), expected);
+    }
+
+    #[test]
+    fn should_use_unsigned_addressing_for_sprites_regardless_of_lcdc_bit_4() {
+        assert_eq!(sprite_row_address(0x80, 0, 0, 0b00000000), 0x8800);
+        assert_eq!(sprite_row_address(0x80, 0, 0, 0b00010000), 0x8800);
+    }
+
+    #[rstest]
+    #[case(0b00000000, TileMapLayer::Background, 0x1800)]
+    #[case(0b00001000, TileMapLayer::Background, 0x1C00)]
+    #[case(0b01000000, TileMapLayer::Window, 0x1C00)]
+    fn should_decode_tile_indices_from_selected_map(
+        #[case] lcdc: u8,
+        #[case] layer: TileMapLayer,
+        #[case] offset: usize,
+    ) {
+        let mut vram = [0; VRAM_SIZE];
+        vram[offset] = 0x12;
+        vram[offset + 1023] = 0x34;
+
+        let indices = decode_tile_map(&vram, lcdc, layer);
+
+        assert_eq!(indices[0], 0x12);
+        assert_eq!(indices[1023], 0x34);
+    }
+
+    #[test]
+    fn should_render_tile_map_pixels_at_tile_positions() {
+        let mut vram = [0; VRAM_SIZE];
+        // Tile 1 at 0x8010 has a solid first row of color 3.
+        vram[0x0010] = 0xFF;
+        vram[0x0011] = 0xFF;
+        // Place tile 1 in column 2, row 1 of the background map at 0x9800.
+        vram[0x1800 + 32 + 2] = 1;
+
+        let mut pixels = [0; TILE_MAP_PIXEL_WIDTH * TILE_MAP_PIXEL_WIDTH];
+        render_tile_map(&vram, 0b00010000, TileMapLayer::Background, &mut pixels);
+
+        let row_start = 8 * TILE_MAP_PIXEL_WIDTH + 16;
+        assert_eq!(pixels[row_start..row_start + 8], [3; 8]);
+        assert_eq!(pixels[row_start + TILE_MAP_PIXEL_WIDTH], 0);
+        assert_eq!(pixels[row_start - 1], 0);
+        assert_eq!(pixels[row_start + 8], 0);
+    }
+
+    #[test]
+    fn should_render_tile_map_with_signed_addressing() {
+        let mut vram = [0; VRAM_SIZE];
+        // Tile 0 at 0x9000 has a solid first row of color 1.
+        vram[0x1000] = 0xFF;
+
+        let mut pixels = [0; TILE_MAP_PIXEL_WIDTH * TILE_MAP_PIXEL_WIDTH];
+        render_tile_map(&vram, 0b00000000, TileMapLayer::Background, &mut pixels);
+
+        assert_eq!(pixels[0..8], [1; 8]);
+        assert_eq!(
+            pixels[TILE_MAP_PIXEL_WIDTH - 8..TILE_MAP_PIXEL_WIDTH],
+            [1; 8]
+        );
+    }
+}
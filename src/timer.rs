@@ -0,0 +1,504 @@
+/*
+    Copyright 2024-2025 notskm
+
+    This file is part of garlicjr.
+
+    garlicjr is free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by the Free
+    Software Foundation, either version 3 of the License, or (at your option)
+    any later version.
+
+    garlicjr is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+    FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+    more details.
+
+    You should have received a copy of the GNU General Public License along
+    with garlicjr. If not, see <https: //www.gnu.org/licenses/>.
+*/
+
+use crate::{unused_bits, TAC};
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Timer {
+    pub interrupt_requested: bool,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    counter: u16,
+    reload_delay: u8,
+    reload_window: u8,
+}
+
+impl Timer {
+    pub fn new() -> Timer {
+        Timer {
+            interrupt_requested: false,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            counter: 0,
+            reload_delay: 0,
+            reload_window: 0,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.reload_window = self.reload_window.saturating_sub(1);
+
+        if self.reload_delay > 0 {
+            self.reload_delay -= 1;
+
+            if self.reload_delay == 0 {
+                self.reload_tima();
+            }
+        }
+
+        let signal = self.timer_signal();
+        self.counter = self.counter.wrapping_add(1);
+        self.detect_falling_edge(signal);
+    }
+
+    pub fn tima(&self) -> u8 {
+        self.tima
+    }
+
+    // A write during the cycles where TIMA reads 0 after overflowing cancels the reload and the
+    // interrupt. A write on the cycle TMA is being loaded is ignored.
+    pub fn write_tima(&mut self, data: u8) {
+        if self.reload_window > 0 {
+            return;
+        }
+
+        self.reload_delay = 0;
+        self.tima = data;
+    }
+
+    pub fn tma(&self) -> u8 {
+        self.tma
+    }
+
+    // TMA is copied into TIMA for the whole reload cycle, so a write during it ends up in both.
+    pub fn write_tma(&mut self, data: u8) {
+        self.tma = data;
+
+        if self.reload_window > 0 {
+            self.tima = data;
+        }
+    }
+
+    pub fn div(&self) -> u8 {
+        (self.counter >> 8) as u8
+    }
+
+    pub fn write_div(&mut self) {
+        let signal = self.timer_signal();
+        self.counter = 0;
+        self.detect_falling_edge(signal);
+    }
+
+    pub fn tac(&self) -> u8 {
+        unused_bits(TAC) | self.tac
+    }
+
+    pub fn write_tac(&mut self, data: u8) {
+        let signal = self.timer_signal();
+        self.tac = data & 0b00000111;
+        self.detect_falling_edge(signal);
+    }
+
+    // TIMA is clocked by a single bit of the internal counter, chosen by TAC and ANDed with the
+    // enable bit. It increments whenever that signal goes from high to low, which means that
+    // resetting the counter or changing TAC can also cause an increment.
+    fn timer_signal(&self) -> bool {
+        let enabled = self.tac & 0b00000100 != 0;
+
+        let bit = match self.tac & 0b00000011 {
+            0b00 => 9,
+            0b01 => 3,
+            0b10 => 5,
+            _ => 7,
+        };
+
+        enabled && self.counter & (1 << bit) != 0
+    }
+
+    fn detect_falling_edge(&mut self, previous_signal: bool) {
+        if previous_signal && !self.timer_signal() {
+            self.increment_tima();
+        }
+    }
+
+    // TIMA reads 0 for 4 cycles after overflowing before TMA is loaded and the interrupt is
+    // requested.
+    fn increment_tima(&mut self) {
+        let (tima, overflowed) = self.tima.overflowing_add(1);
+        self.tima = tima;
+
+        if overflowed {
+            self.reload_delay = 4;
+        }
+    }
+
+    fn reload_tima(&mut self) {
+        self.tima = self.tma;
+        self.interrupt_requested = true;
+        self.reload_window = 4;
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    fn tick_times(timer: &mut Timer, cycles: u32) {
+        for _ in 0..cycles {
+            timer.tick();
+        }
+    }
+
+    fn overflow_tima(timer: &mut Timer) {
+        timer.write_tac(0b00000101);
+        timer.write_tima(0xFF);
+        tick_times(timer, 16);
+    }
+
+    #[test]
+    fn should_initialize_registers_to_0() {
+        let timer = Timer::new();
+        assert_eq!(timer.div(), 0);
+        assert_eq!(timer.tima(), 0);
+        assert_eq!(timer.tma(), 0);
+        assert_eq!(timer.tac(), 0b11111000);
+    }
+
+    #[test]
+    fn should_initialize_registers_to_0_by_default() {
+        let timer = Timer::default();
+        assert_eq!(timer.div(), 0);
+        assert_eq!(timer.tima(), 0);
+        assert_eq!(timer.tma(), 0);
+        assert_eq!(timer.tac(), 0b11111000);
+    }
+
+    #[test]
+    fn should_clone_timer_during_reload() {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        let mut clone = timer.clone();
+        assert_eq!(clone, timer);
+
+        tick_times(&mut timer, 4);
+        tick_times(&mut clone, 4);
+
+        assert_eq!(clone, timer);
+        assert_eq!(clone.tima(), 0x42);
+    }
+
+    #[test]
+    fn should_increment_div_every_256_cycles() {
+        let mut timer = Timer::new();
+
+        tick_times(&mut timer, 255);
+        assert_eq!(timer.div(), 0);
+
+        timer.tick();
+        assert_eq!(timer.div(), 1);
+
+        tick_times(&mut timer, 256);
+        assert_eq!(timer.div(), 2);
+    }
+
+    #[test]
+    fn should_wrap_div_after_255() {
+        let mut timer = Timer::new();
+        tick_times(&mut timer, 256 * 256);
+        assert_eq!(timer.div(), 0);
+    }
+
+    #[test]
+    fn should_reset_div_when_written() {
+        let mut timer = Timer::new();
+        tick_times(&mut timer, 256 * 5 + 100);
+
+        timer.write_div();
+        assert_eq!(timer.div(), 0);
+
+        tick_times(&mut timer, 255);
+        assert_eq!(timer.div(), 0);
+
+        timer.tick();
+        assert_eq!(timer.div(), 1);
+    }
+
+    #[test]
+    fn should_only_store_lower_3_bits_of_tac() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b11111101);
+        assert_eq!(timer.tac(), 0b11111101);
+    }
+
+    #[rstest]
+    #[case(0b00000000, 0b11111000)]
+    #[case(0b00000101, 0b11111101)]
+    #[case(0b11111111, 0b11111111)]
+    fn should_read_unused_tac_bits_as_1(#[case] data: u8, #[case] expected: u8) {
+        let mut timer = Timer::new();
+        timer.write_tac(data);
+        assert_eq!(timer.tac(), expected);
+    }
+
+    #[test]
+    fn should_not_increment_tima_when_disabled() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b00000001);
+
+        tick_times(&mut timer, 4096);
+
+        assert_eq!(timer.tima(), 0);
+    }
+
+    #[rstest]
+    #[case(0b00000100, 1024)]
+    #[case(0b00000101, 16)]
+    #[case(0b00000110, 64)]
+    #[case(0b00000111, 256)]
+    fn should_increment_tima_at_selected_frequency(#[case] tac: u8, #[case] period: u32) {
+        let mut timer = Timer::new();
+        timer.write_tac(tac);
+
+        tick_times(&mut timer, period - 1);
+        assert_eq!(timer.tima(), 0);
+
+        timer.tick();
+        assert_eq!(timer.tima(), 1);
+
+        tick_times(&mut timer, period);
+        assert_eq!(timer.tima(), 2);
+    }
+
+    #[test]
+    fn should_read_0_for_4_cycles_after_tima_overflows() {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        for _ in 0..3 {
+            assert_eq!(timer.tima(), 0);
+            assert!(!timer.interrupt_requested);
+            timer.tick();
+        }
+
+        assert_eq!(timer.tima(), 0);
+        assert!(!timer.interrupt_requested);
+    }
+
+    #[test]
+    fn should_reload_tma_and_request_interrupt_4_cycles_after_tima_overflows() {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        tick_times(&mut timer, 4);
+
+        assert_eq!(timer.tima(), 0x42);
+        assert!(timer.interrupt_requested);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(2)]
+    #[case(3)]
+    fn should_cancel_reload_when_tima_is_written_before_reload(#[case] cycles: u32) {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        tick_times(&mut timer, cycles);
+        timer.write_tima(0x10);
+        tick_times(&mut timer, 4 - cycles);
+
+        assert_eq!(timer.tima(), 0x10);
+        assert!(!timer.interrupt_requested);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(2)]
+    #[case(3)]
+    fn should_ignore_tima_write_during_reload_cycle(#[case] cycles: u32) {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        tick_times(&mut timer, 4 + cycles);
+        timer.write_tima(0x10);
+
+        assert_eq!(timer.tima(), 0x42);
+        assert!(timer.interrupt_requested);
+    }
+
+    #[test]
+    fn should_accept_tima_write_after_reload_cycle() {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        tick_times(&mut timer, 8);
+        timer.write_tima(0x10);
+
+        assert_eq!(timer.tima(), 0x10);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(3)]
+    fn should_load_tma_write_into_tima_during_reload_cycle(#[case] cycles: u32) {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        tick_times(&mut timer, 4 + cycles);
+        timer.write_tma(0x99);
+
+        assert_eq!(timer.tima(), 0x99);
+        assert_eq!(timer.tma(), 0x99);
+    }
+
+    #[test]
+    fn should_reload_with_tma_written_before_reload() {
+        let mut timer = Timer::new();
+        timer.write_tma(0x42);
+        overflow_tima(&mut timer);
+
+        tick_times(&mut timer, 2);
+        timer.write_tma(0x99);
+        tick_times(&mut timer, 2);
+
+        assert_eq!(timer.tima(), 0x99);
+    }
+
+    #[test]
+    fn should_not_load_tma_write_into_tima_outside_reload_cycle() {
+        let mut timer = Timer::new();
+        timer.write_tima(0x10);
+        timer.write_tma(0x99);
+
+        assert_eq!(timer.tima(), 0x10);
+        assert_eq!(timer.tma(), 0x99);
+    }
+
+    #[test]
+    fn should_not_request_interrupt_before_tima_overflows() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b00000101);
+        timer.write_tima(0xFE);
+
+        tick_times(&mut timer, 16);
+
+        assert_eq!(timer.tima(), 0xFF);
+        assert!(!timer.interrupt_requested);
+    }
+
+    // Based on mooneye's div_write and tim*_div_trigger tests.
+    #[rstest]
+    #[case(0b00000100, 512)]
+    #[case(0b00000101, 8)]
+    #[case(0b00000110, 32)]
+    #[case(0b00000111, 128)]
+    fn should_increment_tima_when_div_is_written_while_selected_bit_is_high(
+        #[case] tac: u8,
+        #[case] cycles: u32,
+    ) {
+        let mut timer = Timer::new();
+        timer.write_tac(tac);
+
+        tick_times(&mut timer, cycles);
+        assert_eq!(timer.tima(), 0);
+
+        timer.write_div();
+        assert_eq!(timer.tima(), 1);
+    }
+
+    #[rstest]
+    #[case(0b00000100, 511)]
+    #[case(0b00000101, 7)]
+    #[case(0b00000110, 31)]
+    #[case(0b00000111, 127)]
+    fn should_not_increment_tima_when_div_is_written_while_selected_bit_is_low(
+        #[case] tac: u8,
+        #[case] cycles: u32,
+    ) {
+        let mut timer = Timer::new();
+        timer.write_tac(tac);
+
+        tick_times(&mut timer, cycles);
+        timer.write_div();
+
+        assert_eq!(timer.tima(), 0);
+    }
+
+    #[test]
+    fn should_restart_tima_period_after_div_is_written() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b00000101);
+
+        tick_times(&mut timer, 12);
+        timer.write_div();
+        assert_eq!(timer.tima(), 1);
+
+        tick_times(&mut timer, 15);
+        assert_eq!(timer.tima(), 1);
+
+        timer.tick();
+        assert_eq!(timer.tima(), 2);
+    }
+
+    #[test]
+    fn should_increment_tima_when_disabled_while_selected_bit_is_high() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b00000101);
+
+        tick_times(&mut timer, 8);
+        timer.write_tac(0b00000001);
+
+        assert_eq!(timer.tima(), 1);
+    }
+
+    #[test]
+    fn should_increment_tima_when_switching_to_a_low_bit_from_a_high_bit() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b00000101);
+
+        tick_times(&mut timer, 8);
+        timer.write_tac(0b00000100);
+
+        assert_eq!(timer.tima(), 1);
+    }
+
+    // Based on mooneye's tima_write_reloading test; TIMA writes are plain stores outside of
+    // the overflow window.
+    #[test]
+    fn should_count_from_written_tima_value() {
+        let mut timer = Timer::new();
+        timer.write_tac(0b00000101);
+
+        tick_times(&mut timer, 8);
+        timer.write_tima(0x80);
+        tick_times(&mut timer, 8);
+
+        assert_eq!(timer.tima(), 0x81);
+    }
+}